@@ -15,6 +15,53 @@ stage-all-button = Alle stagen
 commit-button = Committen
 commit-placeholder = Commit-Nachricht eingeben...
 err-no-git-config = Git-Benutzername oder E-Mail nicht konfiguriert.
+err-export-empty = Keine Commits im gewählten Bereich.
+err-export-too-large = Der Graph ist zu groß für ein PNG-Bild. Als SVG exportieren oder einen kleineren Bereich wählen.
+err-signing-key-missing = Kein Signaturschlüssel konfiguriert (user.signingkey).
+err-signing-format = Nicht unterstütztes gpg.format.
+err-signing-failed = Das Signaturprogramm hat keine Signatur geliefert.
+err-worktree-path = Ungültiger Worktree-Ordner.
+err-undo-nothing = Es gibt keine Aktion zum Rückgängigmachen.
+err-undo-moved = Der Branch wurde seit der letzten Aktion verändert und kann nicht automatisch zurückgesetzt werden.
+err-undo-dirty = Bitte Änderungen committen oder stashen, bevor diese Aktion rückgängig gemacht wird.
+err-push-lease = Abgelehnt: Seit dem letzten Fetch hat jemand anderes auf diesen Branch gepusht. Bitte fetchen, die neuen Commits prüfen und dann erneut force-pushen.
+err-rewrite-pushed = Dieser Commit ist bereits im Upstream. Ihn umzuschreiben würde veröffentlichte Historie ändern.
+err-rewrite-merge = Merge-Commits können nicht umgeschrieben werden, ebenso wenig Commits, auf die ein Merge folgt.
+err-rewrite-not-on-branch = Dieser Commit gehört nicht zum aktuellen Branch.
+err-nothing-staged = Es gibt keine gestagten Änderungen zum Committen.
+err-autosquash-nothing = Auf diesem Branch gibt es keine fixup!- oder squash!-Commits mit passendem Ziel.
+err-autosquash-conflict = Die Fixup-Commits kollidieren mit den Commits dazwischen. Autosquash wurde abgebrochen, nichts wurde geändert.
+err-split-empty-part = Jeder Teil einer Aufteilung braucht eine Nachricht und mindestens eine Änderung, die die vorherigen Teile nicht enthalten.
+err-hook-failed = Ein Git-Hook hat den Vorgang abgelehnt. Die Ausgabe steht unter Ansicht > Hook-Protokoll.
+commit-sign = Signieren
+commit-sign-hint = Diesen Commit mit dem Schlüssel aus user.signingkey signieren (Vorgabe: commit.gpgsign)
+commit-bypass-hooks = Hooks umgehen
+commit-bypass-hooks-hint = Die Hooks pre-commit, commit-msg und pre-push überspringen (wie --no-verify)
+commit-subject-placeholder = Betreff (50 Zeichen)
+commit-body-placeholder = Text: was und warum (Umbruch bei 72 Zeichen)
+commit-history-hint = Letzte Nachrichten und Vorlage
+commit-insert-template = Commit-Vorlage einfügen
+commit-history-empty = Keine letzten Nachrichten.
+commit-anyway = Trotzdem committen
+commit-signoff = Sign-off
+commit-signoff-hint = Einen Signed-off-by-Trailer mit deinem Namen und deiner E-Mail anhängen
+commit-add-co-author = Co-Autor
+commit-no-authors = Keine weiteren Autoren in der Historie.
+commit-amend = Amend
+commit-amend-hint = Den letzten Commit durch die gestagten Änderungen und diese Nachricht ersetzen
+commit-amend-button = Amend
+commit-amend-pushed = Der letzte Commit wurde bereits gepusht. Amend schreibt veröffentlichte Historie um und erfordert einen Force Push
+lint-line = Zeile
+lint-subject-empty = Der Betreff ist leer.
+lint-subject-period = Der Betreff sollte nicht mit einem Punkt enden.
+lint-subject-long = Der Betreff ist länger als 50 Zeichen.
+lint-blank-line = Betreff und Text durch eine Leerzeile trennen.
+lint-body-long = Die Zeile ist länger als 72 Zeichen.
+lint-cc-format = Kein Conventional Commit, erwartet wird "typ(scope): beschreibung".
+lint-cc-type = Der Commit-Typ ist nicht erlaubt.
+lint-cc-scope = Der Scope ist nicht erlaubt.
+lint-blocked = Die Commit-Nachricht enthält Fehler und dieses Repository blockiert solche Commits.
+lint-confirm = Die Commit-Nachricht enthält Fehler. "Trotzdem committen" klicken, um sie so zu übernehmen.
 
 # Menü
 m-file = Datei
@@ -25,14 +72,25 @@ mi-settings = Einstellungen
 mi-exit = Beenden
 m-view = Ansicht
 mi-view-all = Alle Commits anzeigen
+mi-show-stashes = Stashes anzeigen
+mi-sort-topo = Topologische Reihenfolge
+mi-sort-date = Nach Datum
+mi-sort-author-date = Nach Autorendatum
+mi-first-parent = Nur erste Eltern (Mainline)
+mi-simplify = Nach Referenzen vereinfachen
+mi-reflog = Reflog
+mi-hook-log = Hook-Protokoll
 m-repo = Repository
 mi-refresh = Aktualisieren
+mi-undo-operation = Letzte Aktion rückgängig...
+mi-snapshots = Sicherungs-Snapshots...
 mi-fetch = Fetch
 mi-pull = Pull
 mi-push = Push
 mi-new-branch = Neuer Branch...
 mi-new-tag = Neuer Tag...
 mi-apply-patch = Patch anwenden...
+mi-export-graph = Graph exportieren...
 mi-explorer = Im Explorer öffnen
 mi-console = Konsole öffnen
 m-window = Fenster
@@ -45,7 +103,8 @@ m-help = Hilfe
 btn-open-repo = Öffnen
 btn-pull = Pull
 btn-push = Push
-btn-force-push = Force Push
+btn-force-push-lease = Force Push (mit Lease)
+btn-force-push = Force Push (überschreiben)
 btn-stash-save = Stash speichern
 btn-stash-pop = Stash anwenden
 
@@ -55,11 +114,54 @@ staged-changes = Bereitgestellte Änderungen (Staged)
 unstaged-changes = Änderungen (Unstaged)
 tab-commit = Commit
 tab-changes = Änderungen
+wip-row = Nicht committete Änderungen ({ $count } Dateien)
+sidebar-hide-remotes = ausblenden
+sidebar-show-remotes = einblenden
+submodule-not-initialized = nicht initialisiert
+sidebar-prune = aufräumen
+sidebar-prune-worktrees = Worktrees entfernen, deren Ordner nicht mehr existiert
+details-contained-in = Enthalten in:
+details-contained-none = keinem Branch oder Tag
+details-signature = Signatur:
+sig-good = Verifizierte Signatur
+sig-untrusted = Signiert, Schlüssel nicht vertrauenswürdig
+sig-unverified = Signiert, Schlüssel nicht verfügbar
+sig-bad = Ungültige Signatur
+sig-unsigned = Nicht signiert
+sig-protected-warning = Auf einem geschützten Branch ohne gültige Signatur
+settings-protected-branches = Geschützte Branches (kommagetrennt)
+settings-confirmations = Vor diesen Aktionen nachfragen
+settings-commit-lint = Commit-Nachrichten
+settings-conventional = Conventional Commits verlangen
+settings-conventional-types = Erlaubte Typen (kommagetrennt)
+settings-conventional-scopes = Erlaubte Scopes (kommagetrennt, leer = alle)
+settings-block-lint = Commits mit Lint-Fehlern blockieren (sonst warnen)
 
 # Modals (Popups)
 modal-reword-title = Commit-Nachricht ändern
 modal-author-title = Autor ändern
+rewrite-preview = Umzuschreibende Commits
+modal-split-title = Commit aufteilen
+split-hint = Dateien oder Hunks für einen Teil auswählen, eine Nachricht vergeben und hinzufügen. Die Teile werden in dieser Reihenfolge aufeinander committet.
+split-hunks = Hunks
+split-part-message = Nachricht dieses Teils
+split-add-part = Teil hinzufügen
+split-rest = Nachricht für die restlichen Änderungen:
+split-confirm = Aufteilen
+diff-unified = Einheitlich
+diff-side-by-side = Nebeneinander
+diff-ignore-ws = Leerzeichen ignorieren
+diff-ignore-ws-hint = Alle Leerzeichen beim Vergleichen von Zeilen ignorieren
+diff-ignore-ws-change = Leerzeichen-Änderungen ignorieren
+diff-ignore-ws-change-hint = Änderungen in der Anzahl der Leerzeichen ignorieren
+diff-ignore-eol = Zeilenende ignorieren
+diff-ignore-eol-hint = Leerzeichen am Zeilenende ignorieren
+diff-context = Kontextzeilen
 modal-clone-title = Repository klonen
+modal-clone-recursive = Submodule rekursiv klonen
+modal-worktree-title = Neuer Worktree
+modal-worktree-name = Ordnername (z. B. review-123)
+modal-worktree-rev = Branch oder Commit
 modal-branch-title = Neuen Branch erstellen
 modal-create = Erstellen
 modal-tag-title = Neuen Tag erstellen
@@ -67,6 +169,53 @@ modal-settings-title = Einstellungen
 modal-cancel = Abbrechen
 modal-save = Speichern
 modal-clone = Klonen
+modal-export-title = Commit-Graph exportieren
+modal-export-hint = Revision oder Bereich (z. B. v1.0..main). Leer lassen für die aktuelle Verlaufsansicht.
+modal-export = Exportieren
+export-done = Graph exportiert nach { $path }
+export-failed = Export fehlgeschlagen: { $error }
+modal-close = Schließen
+modal-reflog-title = Reflog
+reflog-empty = Keine Reflog-Einträge für diese Referenz.
+modal-hook-log-title = Hook-Protokoll
+hook-log-empty = Es wurden noch keine Hooks ausgeführt.
+hook-passed = erfolgreich
+hook-failed = fehlgeschlagen
+hook-log-clear = Leeren
+modal-undo-title = Letzte Aktion rückgängig machen
+modal-undo = Rückgängig
+undo-restored = Diese Commits werden wiederhergestellt:
+undo-removed = Diese Commits werden aus dem Branch entfernt:
+op-commit = Commit
+op-amend = Umformulieren / Ändern
+op-squash = Mit Eltern-Commit zusammenfassen
+op-autosquash = Autosquash
+op-split = Commit aufteilen
+op-reset = Hart zurücksetzen
+modal-snapshots-title = Sicherungs-Snapshots
+modal-snapshots-hint = Nicht committete Änderungen werden vor Hart zurücksetzen, Änderungen verwerfen und Checkout hier gesichert. Beim Wiederherstellen wird das aktuelle Arbeitsverzeichnis ersetzt und vorher ebenfalls gesichert.
+snapshots-empty = Noch keine Snapshots.
+snapshot-restore = Wiederherstellen
+snapshot-delete = Löschen
+snapshots-keep = Behalte
+snapshots-days = Snapshots, max. Tage (0 = unbegrenzt)
+confirm-reset-title = Hart zurücksetzen
+confirm-reset-desc = Den aktuellen Branch verschieben und das Arbeitsverzeichnis überschreiben mit
+confirm-discard-title = Änderungen verwerfen
+confirm-discard-desc = Die nicht gestagten Änderungen verwerfen von
+confirm-delete-branch-title = Branch löschen
+confirm-delete-branch-desc = Den lokalen Branch löschen
+confirm-force-push-title = Force Push (überschreiben)
+confirm-force-push-desc = Den Remote-Branch durch den aktuellen Branch ersetzen.
+confirm-force-push-lease-title = Force Push (mit Lease)
+confirm-force-push-lease-desc = Den Remote-Branch durch den aktuellen Branch ersetzen, sofern seit dem letzten Fetch niemand anderes darauf gepusht hat.
+confirm-files = Nicht committete Änderungen, die überschrieben werden:
+confirm-lost-commits = Commits, die von keinem Branch oder Tag mehr erreichbar sind:
+confirm-remote-commits = Commits auf dem Remote, die überschrieben werden:
+confirm-nothing-lost = Es geht nichts verloren.
+confirm-force-push-fetch-hint = Stand des letzten Fetch; vorher fetchen, um den aktuellen Stand des Remotes zu sehen.
+confirm-force-push-lease-hint = Stand des letzten Fetch. Hat sich der Remote seitdem geändert, wird der Push abgelehnt und nichts überschrieben.
+confirm-dont-ask = Für diese Aktion nicht mehr fragen
 settings-credentials-header = Git Zugangsdaten (HTTPS)
 placeholder-username = Git Benutzername
 placeholder-token = Personal Access Token (PAT)
@@ -75,7 +224,10 @@ btn-close = Schließen
 # Kontextmenü (Rechtsklick)
 menu-reword = Nachricht ändern (Reword)
 menu-author = Autor ändern
+menu-split = Commit aufteilen…
 menu-squash = Mit Parent vereinen (Squash)
+menu-fixup = Fixup für diesen Commit erstellen
+menu-autosquash = Fixups zusammenführen (Autosquash)
 menu-checkout = Auschecken
 menu-cherry = Cherry-Pick
 menu-revert = Revert
@@ -89,7 +241,19 @@ menu-stage = Stagen
 menu-ignore = Ignorieren (.gitignore)
 menu-discard = Änderungen verwerfen
 menu-checkout-branch = Branch auschecken
+menu-solo-branch = Nur diesen Branch anzeigen
+menu-show-reflog = Reflog anzeigen
 menu-del-branch = Branch löschen
+menu-ref-branch-here = Branch hier erstellen
+menu-ref-track = Als lokalen Branch auschecken
+menu-ref-copy = Namen kopieren
+menu-del-tag = Tag löschen
+menu-submodule-init = Submodul initialisieren
+menu-submodule-update = Submodul aktualisieren
+menu-submodule-sync = Submodul-URL synchronisieren
+menu-worktree-here = Neuer Worktree hier
+menu-worktree-lock = Worktree sperren
+menu-worktree-unlock = Worktree entsperren
 
 # Status Nachrichten
 READY = BEREIT
\ No newline at end of file
@@ -15,6 +15,53 @@ stage-all-button = Stage All
 commit-button = Commit
 commit-placeholder = Enter commit message...
 err-no-git-config = Git username or email not configured.
+err-export-empty = No commits in the selected range.
+err-export-too-large = The graph is too large for a PNG image. Export it as SVG or choose a smaller range.
+err-signing-key-missing = No signing key configured (user.signingkey).
+err-signing-format = Unsupported gpg.format.
+err-signing-failed = The signing program returned no signature.
+err-worktree-path = Invalid worktree folder.
+err-undo-nothing = There is no operation to undo.
+err-undo-moved = The branch has moved since the last operation and cannot be undone automatically.
+err-undo-dirty = Commit or stash your changes before undoing this operation.
+err-push-lease = Rejected: someone else has pushed to this branch since your last fetch. Fetch and review their commits, then force push again.
+err-rewrite-pushed = This commit is already on the upstream. Rewriting it would change published history.
+err-rewrite-merge = Merge commits cannot be rewritten, and neither can commits with a merge after them.
+err-rewrite-not-on-branch = This commit is not part of the current branch.
+err-nothing-staged = There are no staged changes to commit.
+err-autosquash-nothing = There are no fixup! or squash! commits with a matching target on this branch.
+err-autosquash-conflict = The fixup commits conflict with the commits in between. Autosquash was cancelled, nothing was changed.
+err-split-empty-part = Every part of a split needs a message and at least one change the previous parts do not have.
+err-hook-failed = A git hook rejected the operation. Its output is in View > Hook Log.
+commit-sign = Sign
+commit-sign-hint = Sign this commit with the key from user.signingkey (defaults to commit.gpgsign)
+commit-bypass-hooks = Bypass hooks
+commit-bypass-hooks-hint = Skip the pre-commit, commit-msg and pre-push hooks (like --no-verify)
+commit-subject-placeholder = Subject (50 characters)
+commit-body-placeholder = Body: what and why (wrap at 72 characters)
+commit-history-hint = Recent messages and template
+commit-insert-template = Insert commit template
+commit-history-empty = No recent messages.
+commit-anyway = Commit anyway
+commit-signoff = Sign-off
+commit-signoff-hint = Add a Signed-off-by trailer with your name and email
+commit-add-co-author = Co-author
+commit-no-authors = No other authors in the history.
+commit-amend = Amend
+commit-amend-hint = Replace the last commit with the staged changes and this message
+commit-amend-button = Amend
+commit-amend-pushed = The last commit has already been pushed. Amending rewrites published history and needs a force push
+lint-line = Line
+lint-subject-empty = The subject is empty.
+lint-subject-period = The subject should not end with a period.
+lint-subject-long = The subject is longer than 50 characters.
+lint-blank-line = Separate subject and body with a blank line.
+lint-body-long = The line is longer than 72 characters.
+lint-cc-format = Not a Conventional Commit, expected "type(scope): description".
+lint-cc-type = Commit type is not in the allowed list.
+lint-cc-scope = Scope is not in the allowed list.
+lint-blocked = The commit message has errors and this repository blocks such commits.
+lint-confirm = The commit message has errors. Click "Commit anyway" to commit it as it is.
 
 # Menu
 m-file = File
@@ -25,14 +72,25 @@ mi-settings = Settings
 mi-exit = Exit
 m-view = View
 mi-view-all = View All Commits
+mi-show-stashes = Show Stashes
+mi-sort-topo = Topological Order
+mi-sort-date = Date Order
+mi-sort-author-date = Author Date Order
+mi-first-parent = First-Parent History
+mi-simplify = Simplify by Decoration
+mi-reflog = Reflog
+mi-hook-log = Hook Log
 m-repo = Repository
 mi-refresh = Refresh
+mi-undo-operation = Undo Last Operation...
+mi-snapshots = Recovery Snapshots...
 mi-fetch = Fetch
 mi-pull = Pull
 mi-push = Push
 mi-new-branch = New Branch...
 mi-new-tag = New Tag...
 mi-apply-patch = Apply Patch...
+mi-export-graph = Export Graph...
 mi-explorer = Open in Explorer
 mi-console = Open Console
 m-window = Window
@@ -45,7 +103,8 @@ m-help = Help
 btn-open-repo = Open
 btn-pull = Pull
 btn-push = Push
-btn-force-push = Force Push
+btn-force-push-lease = Force Push (with lease)
+btn-force-push = Force Push (overwrite)
 btn-stash-save = Stash Save
 btn-stash-pop = Stash Pop
 
@@ -55,11 +114,54 @@ staged-changes = Staged Changes
 unstaged-changes = Unstaged Changes
 tab-commit = Commit
 tab-changes = Changes
+wip-row = Uncommitted changes ({ $count } files)
+sidebar-hide-remotes = hide
+sidebar-show-remotes = show
+submodule-not-initialized = not initialized
+sidebar-prune = prune
+sidebar-prune-worktrees = Prune worktrees whose folder no longer exists
+details-contained-in = Contained in:
+details-contained-none = no branch or tag
+details-signature = Signature:
+sig-good = Verified signature
+sig-untrusted = Signed, key not trusted
+sig-unverified = Signed, key not available
+sig-bad = Invalid signature
+sig-unsigned = Not signed
+sig-protected-warning = On a protected branch without a valid signature
+settings-protected-branches = Protected branches (comma-separated)
+settings-confirmations = Ask for confirmation before
+settings-commit-lint = Commit messages
+settings-conventional = Require Conventional Commits
+settings-conventional-types = Allowed types (comma-separated)
+settings-conventional-scopes = Allowed scopes (comma-separated, empty = any)
+settings-block-lint = Block commits with lint errors (otherwise warn)
 
 # Modals
 modal-reword-title = Reword Commit Message
 modal-author-title = Edit Author
+rewrite-preview = Commits to rewrite
+modal-split-title = Split Commit
+split-hint = Pick files or hunks for a part, give it a message and add it. Parts are committed in this order, each on top of the previous one.
+split-hunks = hunks
+split-part-message = Message of this part
+split-add-part = Add Part
+split-rest = Message for the remaining changes:
+split-confirm = Split
+diff-unified = Unified
+diff-side-by-side = Side by Side
+diff-ignore-ws = Ignore whitespace
+diff-ignore-ws-hint = Ignore all whitespace when comparing lines
+diff-ignore-ws-change = Ignore whitespace changes
+diff-ignore-ws-change-hint = Ignore changes in the amount of whitespace
+diff-ignore-eol = Ignore EOL
+diff-ignore-eol-hint = Ignore whitespace at the end of lines
+diff-context = Context lines
 modal-clone-title = Clone Repository
+modal-clone-recursive = Clone submodules recursively
+modal-worktree-title = New Worktree
+modal-worktree-name = Folder name (e.g. review-123)
+modal-worktree-rev = Branch or commit
 modal-branch-title = Create New Branch
 modal-create = Create
 modal-tag-title = Create New Tag
@@ -67,6 +169,53 @@ modal-settings-title = Settings
 modal-cancel = Cancel
 modal-save = Save
 modal-clone = Clone
+modal-export-title = Export Commit Graph
+modal-export-hint = Revision or range (e.g. v1.0..main). Leave empty for the current history view.
+modal-export = Export
+export-done = Graph exported to { $path }
+export-failed = Export failed: { $error }
+modal-close = Close
+modal-reflog-title = Reflog
+reflog-empty = No reflog entries for this ref.
+modal-hook-log-title = Hook Log
+hook-log-empty = No hooks have run yet.
+hook-passed = passed
+hook-failed = failed
+hook-log-clear = Clear
+modal-undo-title = Undo Last Operation
+modal-undo = Undo
+undo-restored = Commits that will be restored:
+undo-removed = Commits that will be removed from the branch:
+op-commit = Commit
+op-amend = Reword / Amend
+op-squash = Squash with Parent
+op-autosquash = Autosquash
+op-split = Split Commit
+op-reset = Reset Hard
+modal-snapshots-title = Recovery Snapshots
+modal-snapshots-hint = Uncommitted changes are saved here before Reset Hard, Discard Changes and Checkout. Restoring replaces the current working tree, which is snapshotted first.
+snapshots-empty = No snapshots yet.
+snapshot-restore = Restore
+snapshot-delete = Delete
+snapshots-keep = Keep
+snapshots-days = snapshots, max. days (0 = forever)
+confirm-reset-title = Reset Hard
+confirm-reset-desc = Move the current branch and overwrite the working tree with
+confirm-discard-title = Discard Changes
+confirm-discard-desc = Throw away the unstaged changes of
+confirm-delete-branch-title = Delete Branch
+confirm-delete-branch-desc = Delete the local branch
+confirm-force-push-title = Force Push (overwrite)
+confirm-force-push-desc = Replace the remote branch with the current branch.
+confirm-force-push-lease-title = Force Push (with lease)
+confirm-force-push-lease-desc = Replace the remote branch with the current branch, unless someone else has pushed to it since the last fetch.
+confirm-files = Uncommitted changes that will be overwritten:
+confirm-lost-commits = Commits that will no longer be reachable from any branch or tag:
+confirm-remote-commits = Commits on the remote that will be overwritten:
+confirm-nothing-lost = Nothing will be lost.
+confirm-force-push-fetch-hint = Based on the last fetch; fetch first to see the current state of the remote.
+confirm-force-push-lease-hint = Based on the last fetch. If the remote has moved since then, the push is refused and nothing is overwritten.
+confirm-dont-ask = Don't ask again for this action
 settings-credentials-header = Git Credentials (HTTPS)
 placeholder-username = Git Username
 placeholder-token = Personal Access Token (PAT)
@@ -75,7 +224,10 @@ btn-close = Close
 # Context Menu
 menu-reword = Reword Message
 menu-author = Edit Author
+menu-split = Split Commit…
 menu-squash = Squash with Parent
+menu-fixup = Create Fixup for This Commit
+menu-autosquash = Autosquash Fixups
 menu-checkout = Checkout
 menu-cherry = Cherry-Pick
 menu-revert = Revert
@@ -89,7 +241,19 @@ menu-stage = Stage
 menu-ignore = Ignore (.gitignore)
 menu-discard = Discard Changes
 menu-checkout-branch = Checkout Branch
+menu-solo-branch = Solo This Branch
+menu-show-reflog = Show Reflog
 menu-del-branch = Delete Branch
+menu-ref-branch-here = Create Branch Here
+menu-ref-track = Checkout as Local Branch
+menu-ref-copy = Copy Name
+menu-del-tag = Delete Tag
+menu-submodule-init = Init Submodule
+menu-submodule-update = Update Submodule
+menu-submodule-sync = Sync Submodule URL
+menu-worktree-here = New Worktree Here
+menu-worktree-lock = Lock Worktree
+menu-worktree-unlock = Unlock Worktree
 
 # Status Messages
 READY = READY
\ No newline at end of file
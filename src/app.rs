@@ -1,9 +1,15 @@
 use dioxus::prelude::*;
 use crate::dioxus_elements::input_data::MouseButton;
 use crate::i18n::I18nService;
-use crate::git::{GitHandler, GRAPH_COLORS};
-use crate::{load_credentials, save_credentials};
+use crate::git::{ActionPreview, FileDiffOptions, GitHandler, HistoryOptions, SplitFile, SplitPart, GRAPH_COLORS};
+use crate::git::diff::{self, DiffLine, LineKind, Segment};
+use crate::ui::highlight;
+use crate::git::message::{self, ConventionalRules, SUBJECT_LIMIT};
+use crate::git::graph::{ancestry_path, generate_graph, GraphNode, VirtualRow, ROW_HEIGHT, WIP_SHA};
+use crate::git::snapshots::{DEFAULT_EXPIRY_DAYS, DEFAULT_KEEP};
+use crate::{load_credentials, save_credentials, load_repo_settings, save_repo_settings, load_app_settings, save_app_settings, RepoSettings};
 use sys_locale::get_locale;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::process::Command;
 use rfd;
@@ -24,7 +30,12 @@ pub fn app() -> Element {
         }
     });
     let mut refresh_trigger = use_signal(|| 0);
-    let mut commit_msg = use_signal(|| "".to_string());
+    let mut commit_msg = use_signal(|| GitHandler::get_commit_template(&repo_path.read()).unwrap_or_default());
+    let mut lint_confirmed = use_signal(|| false);
+    let mut signoff = use_signal(|| false);
+    let mut amend_mode = use_signal(|| false);
+    let mut co_authors = use_signal(Vec::<String>::new);
+    let mut sign_override = use_signal(|| None::<bool>);
     let mut selected_file = use_signal(|| None::<String>);
     let mut selected_commit = use_signal(|| None::<String>);
     let mut view_mode = use_signal(|| "local".to_string());
@@ -47,18 +58,72 @@ pub fn app() -> Element {
     let mut show_author_modal = use_signal(|| false);
     let mut author_name_input = use_signal(|| "".to_string());
     let mut author_email_input = use_signal(|| "".to_string());
+    let mut rewrite_target = use_signal(|| "".to_string());
+    let mut diff_side_by_side = use_signal(|| load_app_settings().diff_side_by_side);
+    let mut diff_options = use_signal(FileDiffOptions::default);
+    let mut show_split_modal = use_signal(|| false);
+    let mut split_files = use_signal(Vec::<SplitFile>::new);
+    let mut split_parts = use_signal(Vec::<SplitPart>::new);
+    let mut split_selection = use_signal(Vec::<(String, usize)>::new);
+    let mut split_part_msg = use_signal(|| "".to_string());
+    let mut split_rest_msg = use_signal(|| "".to_string());
     
     let mut show_clone_modal = use_signal(|| false);
     let mut clone_url_input = use_signal(|| "".to_string());
+    let mut clone_recursive = use_signal(|| true);
     let mut show_branch_modal = use_signal(|| false);
     let mut modal_branch_name = use_signal(|| "".to_string());
     let mut show_tag_modal = use_signal(|| false);
     let mut modal_tag_name = use_signal(|| "".to_string());
     
+    let mut show_worktree_modal = use_signal(|| false);
+    let mut worktree_name_input = use_signal(|| "".to_string());
+    let mut worktree_rev_input = use_signal(|| "".to_string());
+    let mut show_export_modal = use_signal(|| false);
+    let mut show_reflog = use_signal(|| false);
+    let mut reflog_ref = use_signal(|| "HEAD".to_string());
+    let mut show_undo_modal = use_signal(|| false);
+    let mut show_hook_log = use_signal(|| false);
+    let mut bypass_hooks = use_signal(|| false);
+    let mut show_snapshots = use_signal(|| false);
+    let mut snapshot_keep_input = use_signal(|| "".to_string());
+    let mut snapshot_days_input = use_signal(|| "".to_string());
+    let mut export_range_input = use_signal(|| "".to_string());
+
     let mut show_settings_modal = use_signal(|| false);
+    let mut protected_branches_input = use_signal(|| "".to_string());
+    let mut conventional_input = use_signal(|| false);
+    let mut conventional_types_input = use_signal(|| "".to_string());
+    let mut conventional_scopes_input = use_signal(|| "".to_string());
+    let mut block_lint_input = use_signal(|| false);
+    let mut skip_confirmations_input = use_signal(Vec::<String>::new);
+
+    // Destructive action waiting for confirmation: (kind, target)
+    let mut pending_action = use_signal(|| None::<(String, String)>);
+    // Loaded once when the dialog opens; previews like force push walk every ref
+    let mut pending_preview = use_signal(|| None::<Result<ActionPreview, String>>);
+    let mut confirm_dont_ask = use_signal(|| false);
+    let mut request_action = move |kind: &str, target: String| {
+        if load_app_settings().skip_confirmations.iter().any(|k| k == kind) {
+            let kind = kind.to_string();
+            spawn(async move {
+                let p = repo_path.read().clone(); let u = git_user.read().clone(); let t = git_token.read().clone();
+                let msg = run_destructive_action(&p, &kind, &target, &u, &t, !*bypass_hooks.read(), &i18n_service.read());
+                status_msg.set(msg);
+                let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
+            });
+        } else {
+            confirm_dont_ask.set(false);
+            pending_preview.set(Some(load_action_preview(&repo_path.read(), kind, &target)));
+            pending_action.set(Some((kind.to_string(), target)));
+        }
+    };
 
     let mut zoom_level = use_signal(|| 1.0);
-    let mut view_all_commits = use_signal(|| false);
+    let mut show_stashes = use_signal(|| true);
+    let mut history_sort = use_signal(|| "date".to_string());
+    let mut first_parent_only = use_signal(|| false);
+    let mut simplify_history = use_signal(|| false);
     
     let mut sidebar_width = use_signal(|| 250.0);
     let mut right_panel_width = use_signal(|| 500.0);
@@ -73,22 +138,77 @@ pub fn app() -> Element {
     let branches = GitHandler::get_branches(&current_path).unwrap_or_default();
     let remote_branches = GitHandler::get_remote_branches(&current_path).unwrap_or_default();
     let tags = GitHandler::get_tags(&current_path).unwrap_or_default();
+    let submodules = GitHandler::get_submodules(&current_path).unwrap_or_default();
+    let worktrees = GitHandler::get_worktrees(&current_path).unwrap_or_default();
     
     let repo_name = Path::new(&*current_path).file_name().and_then(|n| n.to_str()).unwrap_or("GitAmicus").to_string();
 
-    let commits_raw: Vec<(String, String, String, String, Vec<String>)> = GitHandler::get_latest_commits_full(&current_path, 100, *view_all_commits.read()).unwrap_or_default();
-    
+    let repo_settings = load_repo_settings(&current_path);
+    let sign_commit = sign_override.read().unwrap_or_else(|| GitHandler::is_signing_enabled(&current_path));
+    let head_ref = format!("refs/heads/{}", current_branch);
+    let history_options = HistoryOptions {
+        sort_mode: history_sort.read().clone(),
+        first_parent: *first_parent_only.read(),
+        simplify_by_decoration: *simplify_history.read(),
+        hidden_refs: repo_settings.hidden_refs.clone(),
+        hide_remotes: repo_settings.hide_remotes,
+    };
+    let commits_raw: Vec<(String, String, String, String, Vec<String>)> = GitHandler::get_latest_commits_full(&current_path, 100, repo_settings.show_all_refs, &history_options).unwrap_or_default();
+    let changes_raw = GitHandler::get_status(&current_path).unwrap_or_default();
+    let stashes = if *show_stashes.read() { GitHandler::get_stashes(&current_path).unwrap_or_default() } else { Vec::new() };
+
+    // Working copy and stash entries are drawn as extra rows of the graph
+    let mut virtual_rows = Vec::new();
+    if !changes_raw.is_empty() {
+        virtual_rows.push(VirtualRow { sha: WIP_SHA.to_string(), parent: GitHandler::get_head_sha(&current_path).ok(), kind: "wip".to_string() });
+    }
+    for (_, _, stash_sha, base_sha) in stashes.iter() {
+        virtual_rows.push(VirtualRow { sha: stash_sha.clone(), parent: Some(base_sha.clone()), kind: "stash".to_string() });
+    }
+
     // Generate the full graph layout from the raw (unfiltered) commits
-    let graph_nodes = crate::git::graph::generate_graph(&commits_raw);
-    let graph_map: std::collections::HashMap<String, crate::git::graph::GraphNode> =
-        graph_nodes.into_iter().map(|node| (node.sha.clone(), node)).collect();
+    let graph_nodes = generate_graph(&commits_raw, &virtual_rows);
 
-    let commits: Vec<_> = commits_raw.clone().into_iter()
-        .filter(|c| c.1.to_lowercase().contains(&commit_search.read().to_lowercase()))
+    let commit_refs = GitHandler::get_commit_refs(&current_path).unwrap_or_default();
+
+    let commit_shas: Vec<String> = commits_raw.iter().map(|c| c.0.clone()).collect();
+    // Checking signatures runs gpg or ssh-keygen, so it happens outside of rendering and
+    // only again when the listed commits, the refs or the refresh trigger change
+    let path_key = current_path.clone();
+    let ref_state = GitHandler::get_ref_state(&current_path);
+    let signature_infos = use_resource(use_reactive((&path_key, &commit_shas, &ref_state), move |(path, shas, _)| {
+        let _ = refresh_trigger.read();
+        async move { GitHandler::get_signature_infos(&path, &shas).unwrap_or_default() }
+    }));
+    let signatures = signature_infos.read().clone().unwrap_or_default();
+    let protected_branches = repo_settings.protected_branches.clone();
+    let protected_memo = use_memo(use_reactive((&path_key, &protected_branches, &commit_shas, &ref_state), |(path, branches, shas, _)| {
+        GitHandler::get_commits_on_branches(&path, &branches, &shas).unwrap_or_default()
+    }));
+    let protected_shas = protected_memo.read().clone();
+
+    let commit_map: HashMap<String, (String, String, String, Vec<String>)> = commits_raw.iter()
+        .map(|(sha, summary, author, time, parents)| (sha.clone(), (summary.clone(), author.clone(), time.clone(), parents.clone())))
+        .collect();
+    let stash_map: HashMap<String, (usize, String)> = stashes.iter()
+        .map(|(index, message, sha, _)| (sha.clone(), (*index, message.clone())))
+        .collect();
+
+    // Ancestors and descendants of the selected commit stay emphasized in the graph
+    let related_shas: Option<HashSet<String>> = selected_commit.read().as_ref()
+        .filter(|sha| commit_map.contains_key(*sha) || stash_map.contains_key(*sha))
+        .map(|sha| ancestry_path(&commits_raw, &virtual_rows, sha));
+    let is_related = |sha: &str| related_shas.as_ref().map(|r| r.contains(sha)).unwrap_or(true);
+
+    let search = commit_search.read().to_lowercase();
+    let graph_rows: Vec<GraphNode> = graph_nodes.into_iter()
+        .filter(|node| match node.kind.as_str() {
+            "commit" => commit_map.get(&node.sha).map(|c| c.0.to_lowercase().contains(&search)).unwrap_or(false),
+            "stash" => stash_map.get(&node.sha).map(|s| s.1.to_lowercase().contains(&search)).unwrap_or(false),
+            _ => true,
+        })
         .collect();
 
-    let changes_raw = GitHandler::get_status(&current_path).unwrap_or_default();
-    
     let staged_files: Vec<String> = changes_raw.iter()
         .filter(|(_, s)| s == "staged")
         .map(|(p, _)| p.clone())
@@ -107,21 +227,146 @@ pub fn app() -> Element {
 
     let diff_content = if *view_mode.read() == "local" {
         if let Some(ref file) = *selected_file.read() {
-            GitHandler::get_file_diff(&current_path, file).unwrap_or_default()
+            GitHandler::get_file_diff(&current_path, file, &diff_options.read()).unwrap_or_default()
         } else { "".to_string() }
     } else {
         if let Some(ref sha) = *selected_commit.read() {
             if let Some(ref file) = *selected_file.read() {
-                GitHandler::get_commit_file_diff(&current_path, sha, file).unwrap_or_default()
+                GitHandler::get_commit_file_diff(&current_path, sha, file, &diff_options.read()).unwrap_or_default()
             } else { "".to_string() }
         } else { "".to_string() }
     };
 
+    let diff_lines = highlight::diff_lines(selected_file.read().as_deref().unwrap_or_default(), &diff_content);
+    let side_by_side = *diff_side_by_side.read();
+    let current_diff_options = diff_options.read().clone();
+    let diff_toolbar = rsx! {
+        div { class: "diff-toolbar",
+            label { class: "commit-option", title: "{i18n.translate(\"diff-ignore-ws-hint\")}",
+                input { r#type: "checkbox", checked: current_diff_options.ignore_whitespace, onchange: move |evt| diff_options.write().ignore_whitespace = evt.checked() }
+                "{i18n.translate(\"diff-ignore-ws\")}"
+            }
+            label { class: "commit-option", title: "{i18n.translate(\"diff-ignore-ws-change-hint\")}",
+                input { r#type: "checkbox", checked: current_diff_options.ignore_whitespace_change, onchange: move |evt| diff_options.write().ignore_whitespace_change = evt.checked() }
+                "{i18n.translate(\"diff-ignore-ws-change\")}"
+            }
+            label { class: "commit-option", title: "{i18n.translate(\"diff-ignore-eol-hint\")}",
+                input { r#type: "checkbox", checked: current_diff_options.ignore_eol, onchange: move |evt| diff_options.write().ignore_eol = evt.checked() }
+                "{i18n.translate(\"diff-ignore-eol\")}"
+            }
+            label { class: "commit-option",
+                "{i18n.translate(\"diff-context\")}"
+                input { r#type: "number", class: "input-modern diff-context-input", min: "0", max: "100",
+                    value: "{current_diff_options.context_lines}",
+                    oninput: move |evt| {
+                        if let Ok(n) = evt.value().trim().parse::<u32>() { diff_options.write().context_lines = n.min(100); }
+                    }
+                }
+            }
+            button { class: "toolbar-btn",
+                onclick: move |_| {
+                    let next = !*diff_side_by_side.read();
+                    diff_side_by_side.set(next);
+                    let mut settings = load_app_settings();
+                    settings.diff_side_by_side = next;
+                    if let Err(e) = save_app_settings(&settings) { status_msg.set(format!("Error: {}", e)); }
+                },
+                if side_by_side { "{i18n.translate(\"diff-unified\")}" } else { "{i18n.translate(\"diff-side-by-side\")}" }
+            }
+        }
+    };
+
     let commit_details = if let Some(ref sha) = *selected_commit.read() {
         GitHandler::get_commit_details(&current_path, sha).ok()
     } else { None };
+    let (message_body, message_trailers) = commit_details.as_ref()
+        .map(|(_, _, msg, _, _)| message::parse_trailers(msg))
+        .unwrap_or_default();
 
-    let svg_view_height = (commits_raw.len() as f64 * 40.0).max(40.0).ceil() as i32;
+    let selected_signature = if let Some(ref sha) = *selected_commit.read() {
+        GitHandler::get_signature_info(&current_path, sha).ok()
+            .map(|info| (i18n.translate(&format!("sig-{}", info.state)), !info.is_valid() && protected_shas.contains(sha), info))
+    } else { None };
+
+    let reflog_entries = if *show_reflog.read() {
+        GitHandler::get_reflog(&current_path, &reflog_ref.read()).unwrap_or_default()
+    } else { Vec::new() };
+
+    let snapshots = if *show_snapshots.read() {
+        GitHandler::get_snapshots(&current_path).unwrap_or_default()
+    } else { Vec::new() };
+
+    let undo_preview = if *show_undo_modal.read() {
+        Some(GitHandler::get_undo_preview(&current_path))
+    } else { None };
+
+    let (commit_subject, commit_body) = message::split(&commit_msg.read());
+    let subject_len = commit_subject.chars().count();
+    let lint_issues = if commit_msg.read().trim().is_empty() { Vec::new() } else { message::lint(&commit_msg.read(), conventional_rules(&repo_settings).as_ref()) };
+    let has_lint_errors = lint_issues.iter().any(|i| i.is_error);
+    let lint_messages: Vec<(bool, String)> = lint_issues.iter()
+        .map(|i| (i.is_error, format!("{} {}: {}", i18n.translate("lint-line"), i.line, i18n.translate(&i.key))))
+        .collect();
+    let commit_label = if has_lint_errors && *lint_confirmed.read() {
+        i18n.translate("commit-anyway")
+    } else if *amend_mode.read() {
+        i18n.translate("commit-amend-button")
+    } else {
+        "Commit".to_string()
+    };
+    let pushed_upstream = if *amend_mode.read() { GitHandler::get_pushed_upstream(&current_path) } else { None };
+    // Reword and author changes replay the commits after the target
+    let (rewrite_note, rewrite_blocked) = if *show_reword_modal.read() || *show_author_modal.read() || *show_split_modal.read() {
+        match GitHandler::get_rewrite_preview(&current_path, &rewrite_target.read()) {
+            Ok(n) => (format!("{}: {}", i18n.translate("rewrite-preview"), n), false),
+            Err(e) => (i18n.translate(&e), true),
+        }
+    } else { (String::new(), false) };
+    // (path, [(hunk index, diff text, part number if already taken, selected)])
+    let split_view: Vec<(String, Vec<(usize, String, Option<usize>, bool)>)> = if *show_split_modal.read() {
+        let parts = split_parts.read();
+        let selection = split_selection.read();
+        split_files.read().iter().map(|f| {
+            let hunks = f.hunks.iter().enumerate().map(|(i, text)| {
+                let key = (f.path.clone(), i);
+                let part = parts.iter().position(|(_, hunks)| hunks.contains(&key)).map(|n| n + 1);
+                (i, text.clone(), part, selection.contains(&key))
+            }).collect();
+            (f.path.clone(), hunks)
+        }).collect()
+    } else { Vec::new() };
+    let split_part_labels: Vec<String> = split_parts.read().iter().enumerate()
+        .map(|(i, (msg, hunks))| format!("#{} {} ({} {})", i + 1, message::split(msg).0, hunks.len(), i18n.translate("split-hunks")))
+        .collect();
+    let show_message_history = *active_menu.read() == Some("message-history".to_string());
+    let recent_messages: Vec<(String, String)> = if show_message_history {
+        GitHandler::get_recent_messages(&current_path).into_iter().map(|m| (message::split(&m).0, m)).collect()
+    } else { Vec::new() };
+    let author_choices: Vec<String> = if *active_menu.read() == Some("co-authors".to_string()) {
+        let chosen = co_authors.read();
+        GitHandler::get_authors(&current_path).unwrap_or_default().into_iter().filter(|a| !chosen.contains(a)).collect()
+    } else { Vec::new() };
+    let commit_template = if show_message_history { GitHandler::get_commit_template(&current_path) } else { None };
+
+    // Newest run first
+    let hook_log: Vec<_> = if *show_hook_log.read() {
+        GitHandler::get_hook_log().into_iter().rev().collect()
+    } else { Vec::new() };
+
+    let action_preview = pending_action.read().clone().map(|(kind, target)| {
+        let preview = pending_preview.read().clone().unwrap_or_else(|| Ok(ActionPreview::default()));
+        let title = i18n.translate(&confirm_title_key(&kind));
+        let description = i18n.translate(&format!("confirm-{}-desc", kind));
+        let action_label = i18n.translate(action_label_key(&kind));
+        (kind, target, preview, title, description, action_label)
+    });
+
+    // Checks every ref against the selection, so only when the selection or the refs change
+    let selected_sha = selected_commit.read().clone();
+    let containing_memo = use_memo(use_reactive((&path_key, &selected_sha, &ref_state), |(path, sha, _)| {
+        sha.map(|sha| GitHandler::get_containing_refs(&path, &sha).unwrap_or_default()).unwrap_or_default()
+    }));
+    let containing_refs = containing_memo.read().clone();
 
     rsx! {
         div {
@@ -216,7 +461,7 @@ pub fn app() -> Element {
                             div { class: "dropdown-item", onclick: move |_| { if let Some(p) = rfd::FileDialog::new().pick_folder() { let new_path = p.display().to_string(); match GitHandler::init(&new_path) { Ok(_) => { status_msg.set("Init success".to_string()); repo_path.set(new_path.clone()); let _ = save_credentials(&git_user.read(), &git_token.read(), &new_path); }, Err(e) => status_msg.set(format!("Init Error: {}", e)), } active_menu.set(None); } }, "{i18n.translate(\"mi-init\")}" }
                             div { class: "dropdown-item", onclick: move |_| { show_clone_modal.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-clone\")}" }
                             div { class: "separator" }
-                            div { class: "dropdown-item", onclick: move |_| { show_settings_modal.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-settings\")}" }
+                            div { class: "dropdown-item", onclick: move |_| { let settings = load_repo_settings(&repo_path.read()); protected_branches_input.set(settings.protected_branches.join(", ")); conventional_input.set(settings.conventional_commits); conventional_types_input.set(settings.conventional_types.join(", ")); conventional_scopes_input.set(settings.conventional_scopes.join(", ")); block_lint_input.set(settings.block_on_lint_errors); skip_confirmations_input.set(load_app_settings().skip_confirmations); show_settings_modal.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-settings\")}" }
                             div { class: "separator" }
                             div { class: "dropdown-item", onclick: move |_| { std::thread::spawn::<_, ()>(|| { std::process::exit(0); }); }, "{i18n.translate(\"mi-exit\")}" }
                         } 
@@ -226,7 +471,18 @@ pub fn app() -> Element {
                     div { class: "menu-item", onmousedown: move |e| e.stop_propagation(), onclick: move |e| { e.stop_propagation(); let current = active_menu.read().clone(); let new_val = if current == Some("view".to_string()) { None } else { Some("view".to_string()) }; active_menu.set(new_val); }, "{i18n.translate(\"m-view\")}" }
                     if *active_menu.read() == Some("view".to_string()) {
                         div { class: "menu-dropdown", onmousedown: move |e| e.stop_propagation(),
-                            div { class: "dropdown-item", onclick: move |_| { let v = *view_all_commits.read(); view_all_commits.set(!v); let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); active_menu.set(None); }, "{i18n.translate(\"mi-view-all\")}" }
+                            div { class: "dropdown-item", onclick: move |_| { let p = repo_path.read().clone(); let _ = update_repo_settings(&p, |s| { s.show_all_refs = !s.show_all_refs; s.hidden_refs.clear(); s.hide_remotes = false; }); let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); active_menu.set(None); }, span { "{i18n.translate(\"mi-view-all\")}" }, span { style: "color: #888;", if repo_settings.show_all_refs && repo_settings.hidden_refs.is_empty() && !repo_settings.hide_remotes { "✓" } else { "" } } }
+                            div { class: "dropdown-item", onclick: move |_| { let v = *show_stashes.read(); show_stashes.set(!v); let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); active_menu.set(None); }, span { "{i18n.translate(\"mi-show-stashes\")}" }, span { style: "color: #888;", if *show_stashes.read() { "✓" } else { "" } } }
+                            div { class: "separator" }
+                            for (mode, label_key) in [("topo", "mi-sort-topo"), ("date", "mi-sort-date"), ("author-date", "mi-sort-author-date")] {
+                                div { class: "dropdown-item", onclick: move |_| { history_sort.set(mode.to_string()); let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); active_menu.set(None); }, span { "{i18n.translate(label_key)}" }, span { style: "color: #888;", if *history_sort.read() == mode { "✓" } else { "" } } }
+                            }
+                            div { class: "separator" }
+                            div { class: "dropdown-item", onclick: move |_| { let v = *first_parent_only.read(); first_parent_only.set(!v); let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); active_menu.set(None); }, span { "{i18n.translate(\"mi-first-parent\")}" }, span { style: "color: #888;", if *first_parent_only.read() { "✓" } else { "" } } }
+                            div { class: "dropdown-item", onclick: move |_| { let v = *simplify_history.read(); simplify_history.set(!v); let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); active_menu.set(None); }, span { "{i18n.translate(\"mi-simplify\")}" }, span { style: "color: #888;", if *simplify_history.read() { "✓" } else { "" } } }
+                            div { class: "separator" }
+                            div { class: "dropdown-item", onclick: move |_| { reflog_ref.set("HEAD".to_string()); show_reflog.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-reflog\")}" }
+                            div { class: "dropdown-item", onclick: move |_| { show_hook_log.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-hook-log\")}" }
                         }
                     }
                 }
@@ -235,14 +491,17 @@ pub fn app() -> Element {
                     if *active_menu.read() == Some("repo".to_string()) {
                         div { class: "menu-dropdown", onmousedown: move |e| e.stop_propagation(),
                             div { class: "dropdown-item", onclick: move |_| { let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); active_menu.set(None); }, span { "{i18n.translate(\"mi-refresh\")}" }, span { style: "color: #888;", "F5" } }
+                            div { class: "dropdown-item", onclick: move |_| { show_undo_modal.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-undo-operation\")}" }
+                            div { class: "dropdown-item", onclick: move |_| { let (keep, days) = GitHandler::get_snapshot_retention(&repo_path.read()); snapshot_keep_input.set(keep.to_string()); snapshot_days_input.set(days.to_string()); show_snapshots.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-snapshots\")}" }
                             div { class: "separator" }
                             div { class: "dropdown-item", onclick: move |_| { active_menu.set(None); spawn(async move { let p = repo_path.read().clone(); let u = git_user.read().clone(); let t = git_token.read().clone(); status_msg.set("Fetching...".to_string()); match GitHandler::fetch(&p, &u, &t) { Ok(_) => status_msg.set("Fetch successful".to_string()), Err(e) => status_msg.set(format!("Fetch Error: {}", e)), } let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); }); }, "{i18n.translate(\"mi-fetch\")}" }
                             div { class: "dropdown-item", onclick: move |_| { active_menu.set(None); spawn(async move { let p = repo_path.read().clone(); let u = git_user.read().clone(); let t = git_token.read().clone(); status_msg.set("Pulling...".to_string()); match GitHandler::pull(&p, &u, &t) { Ok(_) => status_msg.set("Pull successful".to_string()), Err(e) => status_msg.set(format!("Pull Error: {}", e)), } let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); }); }, "{i18n.translate(\"mi-pull\")}" }
-                            div { class: "dropdown-item", onclick: move |_| { active_menu.set(None); spawn(async move { let p = repo_path.read().clone(); let u = git_user.read().clone(); let t = git_token.read().clone(); status_msg.set("Pushing...".to_string()); match GitHandler::push(&p, &u, &t, false) { Ok(_) => status_msg.set("Push successful".to_string()), Err(e) => status_msg.set(format!("Push Error: {}", e)), } let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); }); }, "{i18n.translate(\"mi-push\")}" }
+                            div { class: "dropdown-item", onclick: move |_| { active_menu.set(None); spawn(async move { let p = repo_path.read().clone(); let u = git_user.read().clone(); let t = git_token.read().clone(); status_msg.set("Pushing...".to_string()); match GitHandler::push(&p, &u, &t, "normal", !*bypass_hooks.read()) { Ok(_) => status_msg.set("Push successful".to_string()), Err(e) => { if e == "err-hook-failed" { show_hook_log.set(true); } status_msg.set(format!("Push Error: {}", i18n_service.read().translate(&e))) }, } let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); }); }, "{i18n.translate(\"mi-push\")}" }
                             div { class: "separator" }
                             div { class: "dropdown-item", onclick: move |_| { show_branch_modal.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-new-branch\")}" }
                             div { class: "dropdown-item", onclick: move |_| { show_tag_modal.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-new-tag\")}" }
                             div { class: "dropdown-item", onclick: move |_| { if let Some(f) = rfd::FileDialog::new().pick_file() { let p = repo_path.read().clone(); match GitHandler::apply_patch(&p, &f.display().to_string()) { Ok(_) => { status_msg.set("Patch applied".to_string()); let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); }, Err(e) => status_msg.set(format!("Patch Error: {}", e)), } } active_menu.set(None); }, "{i18n.translate(\"mi-apply-patch\")}" }
+                            div { class: "dropdown-item", onclick: move |_| { show_export_modal.set(true); active_menu.set(None); }, "{i18n.translate(\"mi-export-graph\")}" }
                             div { class: "separator" }
                             div { class: "dropdown-item", onclick: move |_| { let _ = Command::new("explorer").arg(&*repo_path.read()).spawn(); active_menu.set(None); }, "{i18n.translate(\"mi-explorer\")}" }
                             div { class: "dropdown-item", onclick: move |_| { if cfg!(target_os = "windows") { let _ = Command::new("cmd").arg("/C").arg("start").current_dir(&*repo_path.read()).spawn(); } active_menu.set(None); }, "{i18n.translate(\"mi-console\")}" } 
@@ -271,8 +530,9 @@ pub fn app() -> Element {
                     div { class: "toolbar-btn", onmousedown: move |e| e.stop_propagation(), onclick: move |e| { e.stop_propagation(); let current = *show_push_menu.read(); show_push_menu.set(!current); }, span { style: "font-size: 1.2em;", "⬆" }, "{i18n.translate(\"btn-push\")}", " ▾" }
                     if *show_push_menu.read() {
                         div { class: "menu-dropdown", onmousedown: move |e| e.stop_propagation(),
-                            div { class: "dropdown-item", onclick: move |_| { show_push_menu.set(false); spawn(async move { let p = repo_path.read().clone(); let u = git_user.read().clone(); let t = git_token.read().clone(); status_msg.set("Pushing...".to_string()); match GitHandler::push(&p, &u, &t, false) { Ok(_) => status_msg.set("Push successful".to_string()), Err(e) => status_msg.set(format!("Push Error: {}", e)), } let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); }); }, "{i18n.translate(\"btn-push\")}" }
-                            div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { show_push_menu.set(false); spawn(async move { let p = repo_path.read().clone(); let u = git_user.read().clone(); let t = git_token.read().clone(); status_msg.set("Force Pushing...".to_string()); match GitHandler::push(&p, &u, &t, true) { Ok(_) => status_msg.set("Force Push successful".to_string()), Err(e) => status_msg.set(format!("Force Push Error: {}", e)), } let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); }); }, "{i18n.translate(\"btn-force-push\")}" }
+                            div { class: "dropdown-item", onclick: move |_| { show_push_menu.set(false); spawn(async move { let p = repo_path.read().clone(); let u = git_user.read().clone(); let t = git_token.read().clone(); status_msg.set("Pushing...".to_string()); match GitHandler::push(&p, &u, &t, "normal", !*bypass_hooks.read()) { Ok(_) => status_msg.set("Push successful".to_string()), Err(e) => { if e == "err-hook-failed" { show_hook_log.set(true); } status_msg.set(format!("Push Error: {}", i18n_service.read().translate(&e))) }, } let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); }); }, "{i18n.translate(\"btn-push\")}" }
+                            div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { show_push_menu.set(false); request_action("force-push-lease", String::new()); }, "{i18n.translate(\"btn-force-push-lease\")}" }
+                            div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { show_push_menu.set(false); request_action("force-push", String::new()); }, "{i18n.translate(\"btn-force-push\")}" }
                         }
                     }
                 }
@@ -292,6 +552,52 @@ pub fn app() -> Element {
                         span { "{i18n.translate(\"local\")}" }
                         span { class: "badge", style: "margin-left: auto;", "{staged_files.len() + unstaged_files.len()}" }
                     }
+
+                    div { class: "workspace-header", style: "display: flex; align-items: center;",
+                        "WORKTREES"
+                        span { class: "header-action",
+                            title: "{i18n.translate(\"sidebar-prune-worktrees\")}",
+                            onclick: move |_| { let p = repo_path.read().clone(); match GitHandler::prune_worktrees(&p) { Ok(n) => status_msg.set(format!("Pruned {} worktrees", n)), Err(e) => status_msg.set(format!("Worktree Error: {}", e)), } let n = *refresh_trigger.read()+1; refresh_trigger.set(n); },
+                            "{i18n.translate(\"sidebar-prune\")}"
+                        }
+                        span { class: "header-action", style: "margin-left: 10px;",
+                            title: "{i18n.translate(\"modal-worktree-title\")}",
+                            onclick: move |_| { worktree_rev_input.set(GitHandler::get_head_sha(&repo_path.read()).unwrap_or_default()); worktree_name_input.set("".to_string()); show_worktree_modal.set(true); },
+                            "+"
+                        }
+                    }
+                    ul { style: "list-style: none; padding: 0; margin: 0;",
+                        for wt in worktrees.iter() {
+                            {
+                                let is_current = Path::new(&wt.path).canonicalize().ok() == Path::new(&*current_path).canonicalize().ok();
+                                let item_class = if is_current { "nav-item active" } else { "nav-item" };
+                                let icon = if wt.prunable { "⚠" } else if wt.locked.is_some() { "🔒" } else if wt.is_main { "⌂" } else { "⎘" };
+                                let wt_path = wt.path.clone();
+                                let wt_ctx = wt.name.clone();
+                                let wt_prunable = wt.prunable;
+                                let wt_main = wt.is_main;
+                                rsx! {
+                                    li { class: "{item_class}",
+                                        title: "{wt.path}",
+                                        onclick: move |_| {
+                                            if !wt_prunable {
+                                                repo_path.set(wt_path.clone());
+                                                let _ = save_credentials(&git_user.read(), &git_token.read(), &wt_path);
+                                                selected_commit.set(None);
+                                                selected_file.set(None);
+                                                let n = *refresh_trigger.read()+1; refresh_trigger.set(n);
+                                            }
+                                        },
+                                        oncontextmenu: move |evt| { evt.stop_propagation(); if !wt_main { context_menu_pos.set(Some((evt.page_coordinates().x, evt.page_coordinates().y, "worktree".to_string(), wt_ctx.clone()))); } },
+                                        prevent_default: "oncontextmenu",
+                                        span { style: "margin-right: 5px;", "{icon}" }
+                                        "{wt.name}"
+                                        span { class: "submodule-state", "{wt.head}" }
+                                    }
+                                }
+                            }
+                        }
+                    }
                     
                     div { class: "workspace-header", "BRANCHES" } 
                     div { style: "padding: 0 15px 5px 15px; display: flex; gap: 5px;", 
@@ -299,18 +605,24 @@ pub fn app() -> Element {
                         button { class: "btn-icon", onclick: move |_| { let path = repo_path.read().clone(); let name = new_branch_name.read().clone(); if !name.is_empty() { match GitHandler::create_branch(&path, &name) { Ok(_) => { status_msg.set("Branch created".to_string()); new_branch_name.set("".to_string()); let n = *refresh_trigger.read()+1; refresh_trigger.set(n); }, Err(e) => status_msg.set(format!("Error: {}", e)), } } }, "+" }
                     }
                     ul { style: "list-style: none; padding: 0; margin: 0;",
-                        for branch in branches {
+                        for branch in branches.iter().cloned() {
                             {
                                 let is_head = branch == current_branch;
                                 let b_name = branch.clone();
                                 let b_ctx = branch.clone();
                                 let item_class = if is_head { "nav-item active" } else { "nav-item" };
                                 let icon = if is_head { "●" } else { "○" };
+                                let full_ref = format!("refs/heads/{}", branch);
+                                let visible = is_ref_visible(&repo_settings, &full_ref, &head_ref);
                                 rsx! { 
                                     li { class: "{item_class}",
                                         onclick: move |_| { let p = repo_path.read().clone(); match GitHandler::checkout_branch(&p, &b_name) { Ok(_) => { let n = *refresh_trigger.read()+1; refresh_trigger.set(n); }, Err(e) => status_msg.set(format!("Checkout Error: {}", e)), } },
                                         oncontextmenu: move |evt| { evt.stop_propagation(); context_menu_pos.set(Some((evt.page_coordinates().x, evt.page_coordinates().y, "branch".to_string(), b_ctx.clone()))); },
                                         prevent_default: "oncontextmenu",
+                                        input { r#type: "checkbox", class: "ref-visibility", checked: visible,
+                                            onclick: move |e| e.stop_propagation(),
+                                            onchange: move |_| { let p = repo_path.read().clone(); let _ = toggle_ref_visibility(&p, &full_ref); let n = *refresh_trigger.read()+1; refresh_trigger.set(n); }
+                                        }
                                         span { style: "margin-right: 5px;", "{icon}" }
                                         "{branch}" 
                                     } 
@@ -319,9 +631,84 @@ pub fn app() -> Element {
                         }
                     }
                     div { class: "workspace-header", "TAGS" } 
-                    ul { style: "list-style: none; padding: 0; margin: 0;", for tag in tags { { let t_name = tag.clone(); rsx! { li { class: "nav-item", onclick: move |_| { let p = repo_path.read().clone(); match GitHandler::checkout_branch(&p, &t_name) { Ok(_) => { let n = *refresh_trigger.read()+1; refresh_trigger.set(n); }, Err(e) => status_msg.set(format!("Checkout Error: {}", e)), } }, span { "🏷" } "{tag}" } } } } }
-                    div { class: "workspace-header", "REMOTES" }
-                    ul { style: "list-style: none; padding: 0; margin: 0;", for rb in remote_branches { { let r_name = rb.clone(); rsx! { li { class: "nav-item", onclick: move |_| { let p = repo_path.read().clone(); match GitHandler::checkout_branch(&p, &r_name) { Ok(_) => { let n = *refresh_trigger.read()+1; refresh_trigger.set(n); }, Err(e) => status_msg.set(format!("Checkout Error: {}", e)), } }, span { "☁" } "{rb}" } } } } }
+                    ul { style: "list-style: none; padding: 0; margin: 0;",
+                        for tag in tags {
+                            {
+                                let t_name = tag.clone();
+                                let full_ref = format!("refs/tags/{}", tag);
+                                let visible = is_ref_visible(&repo_settings, &full_ref, &head_ref);
+                                rsx! {
+                                    li { class: "nav-item",
+                                        onclick: move |_| { let p = repo_path.read().clone(); match GitHandler::checkout_branch(&p, &t_name) { Ok(_) => { let n = *refresh_trigger.read()+1; refresh_trigger.set(n); }, Err(e) => status_msg.set(format!("Checkout Error: {}", e)), } },
+                                        input { r#type: "checkbox", class: "ref-visibility", checked: visible,
+                                            onclick: move |e| e.stop_propagation(),
+                                            onchange: move |_| { let p = repo_path.read().clone(); let _ = toggle_ref_visibility(&p, &full_ref); let n = *refresh_trigger.read()+1; refresh_trigger.set(n); }
+                                        }
+                                        span { "🏷" } "{tag}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    div { class: "workspace-header", style: "display: flex; align-items: center;",
+                        "REMOTES"
+                        span { class: "header-action",
+                            title: "{i18n.translate(\"sidebar-hide-remotes\")}",
+                            onclick: move |_| { let p = repo_path.read().clone(); let _ = update_repo_settings(&p, |s| { s.show_all_refs = true; s.hide_remotes = !s.hide_remotes; }); let n = *refresh_trigger.read()+1; refresh_trigger.set(n); },
+                            if repo_settings.show_all_refs && repo_settings.hide_remotes { "{i18n.translate(\"sidebar-show-remotes\")}" } else { "{i18n.translate(\"sidebar-hide-remotes\")}" }
+                        }
+                    }
+                    ul { style: "list-style: none; padding: 0; margin: 0;",
+                        for rb in remote_branches {
+                            {
+                                let r_name = rb.clone();
+                                let full_ref = format!("refs/remotes/{}", rb);
+                                let visible = is_ref_visible(&repo_settings, &full_ref, &head_ref);
+                                rsx! {
+                                    li { class: "nav-item",
+                                        onclick: move |_| { let p = repo_path.read().clone(); match GitHandler::checkout_branch(&p, &r_name) { Ok(_) => { let n = *refresh_trigger.read()+1; refresh_trigger.set(n); }, Err(e) => status_msg.set(format!("Checkout Error: {}", e)), } },
+                                        input { r#type: "checkbox", class: "ref-visibility", checked: visible,
+                                            onclick: move |e| e.stop_propagation(),
+                                            onchange: move |_| { let p = repo_path.read().clone(); let _ = toggle_ref_visibility(&p, &full_ref); let n = *refresh_trigger.read()+1; refresh_trigger.set(n); }
+                                        }
+                                        span { "☁" } "{rb}"
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    if !submodules.is_empty() {
+                        div { class: "workspace-header", "SUBMODULES" }
+                        ul { style: "list-style: none; padding: 0; margin: 0;",
+                            for sm in submodules.iter() {
+                                {
+                                    let short = |id: &Option<String>| id.as_ref().map(|i| i[..7].to_string()).unwrap_or_else(|| "-".to_string());
+                                    let in_sync = sm.recorded == sm.checked_out;
+                                    let state = if !sm.initialized {
+                                        i18n.translate("submodule-not-initialized")
+                                    } else if in_sync {
+                                        short(&sm.recorded)
+                                    } else {
+                                        format!("{}\u{2192}{}", short(&sm.recorded), short(&sm.checked_out))
+                                    };
+                                    let state_class = if sm.initialized && in_sync { "submodule-state" } else { "submodule-state out-of-sync" };
+                                    let sm_path = sm.path.clone();
+                                    let sm_ctx = sm.name.clone();
+                                    rsx! {
+                                        li { class: "nav-item",
+                                            title: "{sm.path} ({sm.url})",
+                                            onclick: move |_| { view_mode.set("local".to_string()); right_panel_tab.set("changes".to_string()); selected_commit.set(None); selected_file.set(Some(sm_path.clone())); },
+                                            oncontextmenu: move |evt| { evt.stop_propagation(); context_menu_pos.set(Some((evt.page_coordinates().x, evt.page_coordinates().y, "submodule".to_string(), sm_ctx.clone()))); },
+                                            prevent_default: "oncontextmenu",
+                                            span { style: "margin-right: 5px;", "⧉" }
+                                            "{sm.name}"
+                                            span { class: "{state_class}", "{state}" }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
                 }
                 
                 div { class: "resizer", onmousedown: move |_| dragging_sidebar.set(true) }
@@ -345,17 +732,118 @@ pub fn app() -> Element {
                                 }
                             }
                             tbody {
-                                for (sha, summary, author, time, parents) in commits.iter() {
+                                for node in graph_rows.iter() {
                                     {
-                                        if let Some(node) = graph_map.get(sha) {
+                                        let row_top = node.row as f64 * ROW_HEIGHT;
+                                        let node_stroke = GRAPH_COLORS[node.color_index];
+                                        let node_fill = if node.kind == "stash" { node_stroke } else { "var(--bg-base)" };
+                                        let node_dash = if node.kind == "wip" { "3 2" } else { "none" };
+                                        let node_related = is_related(&node.sha);
+                                        let node_opacity = if node_related { "1" } else { "0.25" };
+                                        let dim_class = if node_related { "" } else { " dimmed" };
+                                        let path_opacity: Vec<&str> = node.path_parents.iter()
+                                            .map(|p| if node_related && is_related(p) { "1" } else { "0.25" })
+                                            .collect();
+                                        let through_opacity: Vec<&str> = node.through_edges.iter()
+                                            .map(|(child, parent)| if is_related(child) && is_related(parent) { "1" } else { "0.25" })
+                                            .collect();
+                                        let graph_cell = rsx! {
+                                            td { class: "commit-graph-cell-svg",
+                                                svg {
+                                                    height: "40px",
+                                                    width: "245px",
+                                                    view_box: "0 {row_top} 245 {ROW_HEIGHT}",
+                                                    preserve_aspect_ratio: "xMinYMid slice",
+                                                    for (idx, path_d) in node.through_paths.iter().enumerate() {
+                                                        path {
+                                                            d: "{path_d}",
+                                                            stroke: "{GRAPH_COLORS[*node.through_colors.get(idx).unwrap_or(&0)]}",
+                                                            opacity: "{through_opacity.get(idx).unwrap_or(&\"1\")}",
+                                                            "stroke-width": "4",
+                                                            fill: "none",
+                                                            "stroke-linecap": "round",
+                                                            "stroke-linejoin": "round"
+                                                        }
+                                                    }
+                                                    for (idx, path_d) in node.paths.iter().enumerate() {
+                                                        path { 
+                                                            d: "{path_d}", 
+                                                            stroke: "{GRAPH_COLORS[*node.path_colors.get(idx).unwrap_or(&node.color_index)]}", 
+                                                            opacity: "{path_opacity.get(idx).unwrap_or(&\"1\")}",
+                                                            "stroke-width": "4", 
+                                                            fill: "none",
+                                                            "stroke-linecap": "round",
+                                                            "stroke-linejoin": "round"
+                                                        }
+                                                    }
+                                                    circle {
+                                                        cx: "{node.cx}",
+                                                        cy: "{node.cy}",
+                                                        r: "{node.r}",
+                                                        fill: "{node_fill}",
+                                                        stroke: "{node_stroke}",
+                                                        "stroke-width": "3",
+                                                        "stroke-dasharray": "{node_dash}",
+                                                        opacity: "{node_opacity}"
+                                                    }
+                                                }
+                                            }
+                                        };
+
+                                        if node.kind == "wip" {
+                                            let wip_label = i18n.translate_with("wip-row", &[("count", &changes_raw.len().to_string())]);
+                                            rsx! {
+                                                tr {
+                                                    class: "wip-row{dim_class}",
+                                                    onclick: move |_| {
+                                                        view_mode.set("local".to_string());
+                                                        right_panel_tab.set("commit".to_string());
+                                                        selected_commit.set(None);
+                                                        selected_file.set(None);
+                                                    },
+                                                    {graph_cell}
+                                                    td { colspan: 3, "{wip_label}" }
+                                                }
+                                            }
+                                        } else if let Some((index, message)) = stash_map.get(&node.sha) {
+                                            let is_sel = Some(node.sha.clone()) == *selected_commit.read();
+                                            let bg_val = if is_sel { "var(--table-hover)" } else { "transparent" };
+                                            let sha_click = node.sha.clone();
+                                            rsx! {
+                                                tr {
+                                                    class: "stash-row{dim_class}",
+                                                    style: "background: {bg_val};",
+                                                    onclick: move |_| {
+                                                        selected_commit.set(Some(sha_click.clone()));
+                                                        view_mode.set("history".to_string());
+                                                        selected_file.set(None);
+                                                    },
+                                                    {graph_cell}
+                                                    td { colspan: 3,
+                                                        span { class: "ref-badge", style: "border-color: {node_stroke};", "📦 stash@{{{index}}}" }
+                                                        "{message}"
+                                                    }
+                                                }
+                                            }
+                                        } else if let Some((summary, author, time, parents)) = commit_map.get(&node.sha) {
+                                            let sha = &node.sha;
                                             let is_sel = Some(sha.clone()) == *selected_commit.read();
                                             let bg_val = if is_sel { "var(--table-hover)" } else { "transparent" };
                                             let sha_click = sha.clone();
                                             let sha_ctx = sha.clone();
-                                            let summary_clone = summary.clone();
                                             let parents_clone = parents.clone();
+                                            let signature = signatures.get(sha).cloned();
+                                            let sig_state = signature.as_ref().map(|s| s.state.clone()).unwrap_or_else(|| "unsigned".to_string());
+                                            let sig_flagged = protected_shas.contains(sha) && !signature.as_ref().map(|s| s.is_valid()).unwrap_or(false);
+                                            let sig_icon = match sig_state.as_str() { "good" => "🔏", "bad" => "✗", "unsigned" => "⚠", _ => "?" };
+                                            let sig_title = match signature.as_ref().filter(|s| !s.signer.is_empty()) {
+                                                Some(s) => format!("{} ({})", i18n.translate(&format!("sig-{}", sig_state)), s.signer),
+                                                None => i18n.translate(&format!("sig-{}", sig_state)),
+                                            };
+                                            let flag_class = if sig_flagged { " sig-flagged" } else { "" };
                                             rsx! {
                                                 tr {
+                                                    class: "{dim_class}{flag_class}",
                                                     style: "background: {bg_val}; height: 28px; cursor: pointer;",
                                                     onclick: move |_| {
                                                         selected_commit.set(Some(sha_click.clone()));
@@ -366,36 +854,49 @@ pub fn app() -> Element {
                                                         evt.stop_propagation();
                                                         if parents_clone.len() <= 1 {
                                                             context_menu_pos.set(Some((evt.page_coordinates().x, evt.page_coordinates().y, "commit".to_string(), sha_ctx.clone())));
-                                                            reword_input.set(summary_clone.clone());
                                                         }
                                                     },
                                                     prevent_default: "oncontextmenu",
-                                                    td { class: "commit-graph-cell-svg",
-                                                        svg {
-                                                            height: "40px",
-                                                            width: "245px",
-                                                            viewBox: "0 0 245 {svg_view_height}",
-                                                            for (idx, path_d) in node.paths.iter().enumerate() {
-                                                                path { 
-                                                                    d: "{path_d}", 
-                                                                    stroke: "{GRAPH_COLORS[*node.path_colors.get(idx).unwrap_or(&node.color_index)]}", 
-                                                                    "stroke-width": "4", 
-                                                                    fill: "none",
-                                                                    "stroke-linecap": "round",
-                                                                    "stroke-linejoin": "round"
+                                                    {graph_cell}
+                                                    td {
+                                                        if sig_state != "unsigned" || sig_flagged {
+                                                            span { class: "sig-badge sig-{sig_state}", title: "{sig_title}", "{sig_icon}" }
+                                                        }
+                                                        for label in commit_refs.get(sha).cloned().unwrap_or_default() {
+                                                            {
+                                                                let lane_color = GRAPH_COLORS[node.color_index];
+                                                                let icon = match label.kind.as_str() { "remote" => "☁", "tag" => "🏷", "head" => "◉", _ => "⎇" };
+                                                                let badge_class = if label.is_head || label.kind == "head" { "ref-badge head" } else { "ref-badge" };
+                                                                let badge_bg = if label.kind == "tag" { "transparent".to_string() } else { format!("{}33", lane_color) };
+                                                                let r_click = label.clone();
+                                                                let r_ctx = label.clone();
+                                                                rsx! {
+                                                                    span {
+                                                                        class: "{badge_class}",
+                                                                        style: "border-color: {lane_color}; background: {badge_bg};",
+                                                                        title: "{label.name}",
+                                                                        onclick: move |evt| {
+                                                                            evt.stop_propagation();
+                                                                            let p = repo_path.read().clone();
+                                                                            let res = if r_click.kind == "head" { Ok(()) } else { GitHandler::checkout_branch(&p, &r_click.name) };
+                                                                            match res {
+                                                                                Ok(_) => { let n = *refresh_trigger.read() + 1; refresh_trigger.set(n); },
+                                                                                Err(e) => status_msg.set(format!("Checkout Error: {}", e)),
+                                                                            }
+                                                                        },
+                                                                        oncontextmenu: move |evt| {
+                                                                            evt.stop_propagation();
+                                                                            context_menu_pos.set(Some((evt.page_coordinates().x, evt.page_coordinates().y, format!("ref_{}", r_ctx.kind), r_ctx.name.clone())));
+                                                                        },
+                                                                        prevent_default: "oncontextmenu",
+                                                                        span { style: "color: {lane_color};", "{icon}" }
+                                                                        "{label.name}"
+                                                                    }
                                                                 }
                                                             }
-                                                            circle {
-                                                                cx: "{node.cx}",
-                                                                cy: "{node.cy}",
-                                                                r: "{node.r}",
-                                                                fill: "var(--bg-base)",
-                                                                stroke: "{GRAPH_COLORS[node.color_index]}",
-                                                                "stroke-width": "3"
-                                                            }
                                                         }
+                                                        "{summary}"
                                                     }
-                                                    td { "{summary}" }
                                                     td { "{time}" }
                                                     td { "{author}" }
                                                 }
@@ -437,32 +938,176 @@ pub fn app() -> Element {
                                 
                                 div {
                                     style: "padding: 15px; border-bottom: 1px solid var(--border-color); background: var(--bg-surface); display: flex; flex-direction: column; gap: 10px; flex-shrink: 0;",
+                                    div { style: "display: flex; align-items: center; gap: 8px;",
+                                        input {
+                                            class: "input-modern commit-subject",
+                                            placeholder: "{i18n.translate(\"commit-subject-placeholder\")}",
+                                            value: "{commit_subject}",
+                                            oninput: move |evt| {
+                                                let (_, body) = message::split(&commit_msg.read());
+                                                commit_msg.set(message::join(&evt.value(), &body));
+                                                lint_confirmed.set(false);
+                                            }
+                                        }
+                                        span { class: if subject_len > SUBJECT_LIMIT { "subject-counter over" } else { "subject-counter" }, "{subject_len}/{SUBJECT_LIMIT}" }
+                                        div { style: "position: relative;",
+                                            div { class: "toolbar-btn", title: "{i18n.translate(\"commit-history-hint\")}", onmousedown: move |e| e.stop_propagation(), onclick: move |_| { let open = *active_menu.read() == Some("message-history".to_string()); active_menu.set(if open { None } else { Some("message-history".to_string()) }); }, "⟲ ▾" }
+                                            if show_message_history {
+                                                div { class: "menu-dropdown", style: "left: auto; right: 0; width: 320px; max-height: 300px; overflow-y: auto;", onmousedown: move |e| e.stop_propagation(),
+                                                    if let Some(template) = commit_template {
+                                                        div { class: "dropdown-item", onclick: move |_| { commit_msg.set(template.clone()); lint_confirmed.set(false); active_menu.set(None); }, "{i18n.translate(\"commit-insert-template\")}" }
+                                                        div { class: "separator" }
+                                                    }
+                                                    if recent_messages.is_empty() {
+                                                        div { style: "padding: 6px 12px; color: var(--text-sub); font-size: 0.85em;", "{i18n.translate(\"commit-history-empty\")}" }
+                                                    }
+                                                    for (subject, full) in recent_messages {
+                                                        div { class: "dropdown-item", title: "{full}", style: "white-space: nowrap; overflow: hidden; text-overflow: ellipsis; display: block;", onclick: move |_| { commit_msg.set(full.clone()); lint_confirmed.set(false); active_menu.set(None); }, "{subject}" }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
                                     textarea {
-                                        class: "input-modern",
-                                        style: "width: 100%; padding: 10px; border: 1px solid var(--border-color); border-radius: 4px; height: 70px; font-size: 0.9em; resize: none; font-family: 'Inter', sans-serif; background: var(--bg-surface); color: white; outline: none;",
-                                        placeholder: "Commit message...",
-                                        value: "{commit_msg}",
-                                        oninput: move |evt| commit_msg.set(evt.value())
+                                        class: "input-modern commit-body",
+                                        placeholder: "{i18n.translate(\"commit-body-placeholder\")}",
+                                        value: "{commit_body}",
+                                        oninput: move |evt| {
+                                            let (subject, _) = message::split(&commit_msg.read());
+                                            commit_msg.set(message::join(&subject, &evt.value()));
+                                            lint_confirmed.set(false);
+                                        }
+                                    }
+                                    div { class: "trailer-options",
+                                        label {
+                                            class: "commit-option",
+                                            title: "{i18n.translate(\"commit-amend-hint\")}",
+                                            input {
+                                                r#type: "checkbox",
+                                                checked: *amend_mode.read(),
+                                                onchange: move |evt| {
+                                                    let amend = evt.checked();
+                                                    amend_mode.set(amend);
+                                                    let path = repo_path.read().clone();
+                                                    // Amending starts from the message being replaced
+                                                    if amend {
+                                                        if let Ok((_, _, head_msg, _, _)) = GitHandler::get_commit_details(&path, "HEAD") {
+                                                            commit_msg.set(head_msg.trim_end().to_string());
+                                                        }
+                                                    } else {
+                                                        commit_msg.set(GitHandler::get_commit_template(&path).unwrap_or_default());
+                                                    }
+                                                    lint_confirmed.set(false);
+                                                }
+                                            }
+                                            "{i18n.translate(\"commit-amend\")}"
+                                        }
+                                        label {
+                                            class: "commit-option",
+                                            title: "{i18n.translate(\"commit-signoff-hint\")}",
+                                            input { r#type: "checkbox", checked: *signoff.read(), onchange: move |evt| signoff.set(evt.checked()) }
+                                            "{i18n.translate(\"commit-signoff\")}"
+                                        }
+                                        for author in co_authors.read().clone() {
+                                            span { class: "co-author-chip", title: "Co-authored-by: {author}",
+                                                "{author}"
+                                                span { class: "chip-remove", onclick: move |_| co_authors.write().retain(|a| *a != author), "×" }
+                                            }
+                                        }
+                                        div { style: "position: relative;",
+                                            div { class: "toolbar-btn", onmousedown: move |e| e.stop_propagation(), onclick: move |_| { let open = *active_menu.read() == Some("co-authors".to_string()); active_menu.set(if open { None } else { Some("co-authors".to_string()) }); }, "{i18n.translate(\"commit-add-co-author\")} ▾" }
+                                            if *active_menu.read() == Some("co-authors".to_string()) {
+                                                div { class: "menu-dropdown", style: "width: 320px; max-height: 300px; overflow-y: auto;", onmousedown: move |e| e.stop_propagation(),
+                                                    if author_choices.is_empty() {
+                                                        div { style: "padding: 6px 12px; color: var(--text-sub); font-size: 0.85em;", "{i18n.translate(\"commit-no-authors\")}" }
+                                                    }
+                                                    for author in author_choices {
+                                                        div { class: "dropdown-item", style: "display: block; white-space: nowrap; overflow: hidden; text-overflow: ellipsis;", onclick: move |_| { co_authors.write().push(author.clone()); active_menu.set(None); }, "{author}" }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    if let Some(upstream) = pushed_upstream {
+                                        div { class: "amend-warning", "⚠ {i18n.translate(\"commit-amend-pushed\")} ({upstream})" }
+                                    }
+                                    if !lint_messages.is_empty() {
+                                        div { class: "lint-list",
+                                            for (is_error, text) in lint_messages {
+                                                div { class: if is_error { "lint-issue error" } else { "lint-issue" }, if is_error { "✖ " } else { "⚠ " } "{text}" }
+                                            }
+                                        }
                                     }
                                     div {
-                                        style: "display: flex; justify-content: flex-end; gap: 10px;",
+                                        style: "display: flex; justify-content: flex-end; align-items: center; gap: 10px;",
+                                        label {
+                                            class: "commit-option",
+                                            title: "{i18n.translate(\"commit-sign-hint\")}",
+                                            input {
+                                                r#type: "checkbox",
+                                                checked: sign_commit,
+                                                onchange: move |evt| sign_override.set(Some(evt.checked()))
+                                            }
+                                            "{i18n.translate(\"commit-sign\")}"
+                                        }
+                                        label {
+                                            class: "commit-option",
+                                            title: "{i18n.translate(\"commit-bypass-hooks-hint\")}",
+                                            input {
+                                                r#type: "checkbox",
+                                                checked: *bypass_hooks.read(),
+                                                onchange: move |evt| bypass_hooks.set(evt.checked())
+                                            }
+                                            "{i18n.translate(\"commit-bypass-hooks\")}"
+                                        }
                                         button {
                                             class: "btn-primary",
                                             onclick: move |_| {
-                                                let msg = commit_msg.read().clone();
                                                 let path = repo_path.read().clone();
+                                                let mut msg = message::cleanup(&commit_msg.read());
+                                                if !msg.is_empty() {
+                                                    if *signoff.read() {
+                                                        match GitHandler::get_signoff_identity(&path) {
+                                                            Ok(identity) => msg = message::add_trailer(&msg, "Signed-off-by", &identity),
+                                                            Err(e) => { status_msg.set(format!("Commit Error: {}", i18n_service.read().translate(&e))); return; }
+                                                        }
+                                                    }
+                                                    for author in co_authors.read().iter() {
+                                                        msg = message::add_trailer(&msg, "Co-authored-by", author);
+                                                    }
+                                                }
+                                                let settings = load_repo_settings(&path);
+                                                if message::lint(&msg, conventional_rules(&settings).as_ref()).iter().any(|i| i.is_error) {
+                                                    if settings.block_on_lint_errors {
+                                                        status_msg.set(i18n_service.read().translate("lint-blocked"));
+                                                        return;
+                                                    }
+                                                    if !*lint_confirmed.read() {
+                                                        lint_confirmed.set(true);
+                                                        status_msg.set(i18n_service.read().translate("lint-confirm"));
+                                                        return;
+                                                    }
+                                                }
                                                 if !msg.is_empty() {
-                                                    match GitHandler::create_commit(&path, &msg) {
+                                                    let amend = *amend_mode.read();
+                                                    match GitHandler::create_commit(&path, &msg, sign_commit, !*bypass_hooks.read(), amend) {
                                                         Ok(_) => { 
-                                                            status_msg.set("Committed".to_string()); 
-                                                            commit_msg.set("".to_string());
+                                                            status_msg.set(if amend { "Amended".to_string() } else { "Committed".to_string() });
+                                                            amend_mode.set(false);
+                                                            commit_msg.set(GitHandler::get_commit_template(&path).unwrap_or_default());
+                                                            lint_confirmed.set(false);
+                                                            co_authors.write().clear();
+                                                            sign_override.set(None);
                                                             let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
                                                         },
-                                                        Err(e) => status_msg.set(format!("Commit Error: {}", e)),
+                                                        Err(e) => {
+                                                            if e == "err-hook-failed" { show_hook_log.set(true); }
+                                                            status_msg.set(format!("Commit Error: {}", i18n_service.read().translate(&e)));
+                                                        }
                                                     }
                                                 }
                                             },
-                                            "Commit"
+                                            "{commit_label}"
                                         }
                                     }
                                 }
@@ -538,17 +1183,13 @@ pub fn app() -> Element {
                                 }
                             }
                         } else {
+                            {diff_toolbar}
                             div { style: "flex: 1; background: #1e1e2e; color: #cdd6f4; overflow: auto; font-family: 'JetBrains Mono', monospace; font-size: 0.85em; padding: 10px; min-height: 0;",
-                                for line in diff_content.lines() {
-                                    {
-                                        let line_bg = if line.starts_with('+') { "rgba(166, 227, 161, 0.2)" } else if line.starts_with('-') { "rgba(243, 139, 168, 0.2)" } else { "transparent" };
-                                        rsx! { div { style: "background: {line_bg}; line-height: 1.4; white-space: pre;", "{line}" } }
-                                    }
-                                }
+                                {diff_view(&diff_lines, side_by_side)}
                             }
                         }
                     } else {
-                        if let Some((author, committer, msg, sha, parents)) = commit_details {
+                        if let Some((author, committer, _, sha, parents)) = commit_details {
                             div { style: "flex: 1; display: flex; flex-direction: column; overflow: hidden;",
                                 div { style: "padding: 20px; border-bottom: 1px solid var(--border-color); background: var(--bg-surface); flex-shrink: 0;",
                                     div { style: "display: flex; gap: 15px; margin-bottom: 15px;",
@@ -558,9 +1199,43 @@ pub fn app() -> Element {
                                             div { style: "font-size: 0.85em; color: var(--text-sub); margin-top: 2px;", "Committed by {committer}" }
                                             div { style: "font-size: 0.8em; color: var(--accent-primary); margin-top: 6px; font-family: 'JetBrains Mono', monospace; background: rgba(0,0,0,0.2); padding: 2px 5px; border-radius: 3px; display: inline-block;", "{sha}" }
                                             div { style: "font-size: 0.8em; color: var(--text-sub); margin-top: 2px;", "Parents: {parents}" }
+                                            if let Some((sig_label, flagged, info)) = selected_signature.clone() {
+                                                div { class: "signature-line sig-{info.state}",
+                                                    span { style: "margin-right: 6px; color: var(--text-sub);", "{i18n.translate(\"details-signature\")}" }
+                                                    "{sig_label}"
+                                                    if !info.format.is_empty() {
+                                                        span { style: "color: var(--text-sub); margin-left: 6px;", "({info.format})" }
+                                                    }
+                                                    if !info.signer.is_empty() {
+                                                        span { style: "margin-left: 6px; font-family: 'JetBrains Mono', monospace;", "{info.signer}" }
+                                                    }
+                                                    if flagged {
+                                                        div { class: "sig-warning", "⚠ {i18n.translate(\"sig-protected-warning\")}" }
+                                                    }
+                                                }
+                                            }
+                                            div { class: "contained-in",
+                                                span { style: "margin-right: 6px;", "{i18n.translate(\"details-contained-in\")}" }
+                                                if containing_refs.is_empty() {
+                                                    span { style: "font-style: italic;", "{i18n.translate(\"details-contained-none\")}" }
+                                                }
+                                                for label in containing_refs.iter() {
+                                                    {
+                                                        let icon = match label.kind.as_str() { "remote" => "☁", "tag" => "🏷", _ => "⎇" };
+                                                        rsx! { span { class: "ref-badge", style: "border-color: var(--border-color);", "{icon} {label.name}" } }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                    div { style: "white-space: pre-wrap; font-size: 0.95em; line-height: 1.5; color: var(--text-main); background: rgba(0,0,0,0.2); padding: 12px; border-radius: 6px; border: 1px solid var(--border-color);", "{message_body}" }
+                                    if !message_trailers.is_empty() {
+                                        div { class: "trailer-list",
+                                            for (token, value) in message_trailers.iter() {
+                                                div { class: "trailer", span { class: "trailer-key", "{token}" } span { "{value}" } }
+                                            }
                                         }
                                     }
-                                    div { style: "white-space: pre-wrap; font-size: 0.95em; line-height: 1.5; color: var(--text-main); background: rgba(0,0,0,0.2); padding: 12px; border-radius: 6px; border: 1px solid var(--border-color);", "{msg}" }
                                 }
                                 div { style: "padding: 8px 15px; background: var(--bg-header); font-weight: 600; font-size: 0.75em; border-bottom: 1px solid var(--border-color); color: var(--text-sub); flex-shrink: 0;", "CHANGED FILES" }
                                 ul { style: "list-style: none; padding: 0; margin: 0; flex: 0.3; overflow-y: auto; background: var(--bg-base); min-height: 0;",
@@ -580,13 +1255,9 @@ pub fn app() -> Element {
                                     }
                                 }
 
-                                div { style: "flex: 0.7; background: #1e1e2e; color: #cdd6f4; overflow: auto; font-family: 'JetBrains Mono', monospace; font-size: 0.8em; border-top: 1px solid var(--border-color); min-height: 0; padding: 10px;",
-                                    for line in diff_content.lines() {
-                                        {
-                                            let line_bg = if line.starts_with('+') { "rgba(166, 227, 161, 0.2)" } else if line.starts_with('-') { "rgba(243, 139, 168, 0.2)" } else { "transparent" };
-                                            rsx! { div { style: "background: {line_bg}; line-height: 1.4; white-space: pre;", "{line}" } }
-                                        }
-                                    }
+                                {diff_toolbar}
+                                div { style: "flex: 0.7; background: #1e1e2e; color: #cdd6f4; overflow: auto; font-family: 'JetBrains Mono', monospace; font-size: 0.8em; min-height: 0; padding: 10px;",
+                                    {diff_view(&diff_lines, side_by_side)}
                                 }
                             }
                         } else {
@@ -608,24 +1279,51 @@ pub fn app() -> Element {
                     let t8_ignore = target.clone();
                     let t9_checkout_b = target.clone();
                     let t10_del_b = target.clone();
+                    let t_solo = target.clone();
+                    let t_reflog = target.clone();
                     let t_patch = target.clone();
                     let t_info = target.clone();
                     let t_file_staged = target.clone();
                     let t_file_unstaged = target.clone();
+                    let t_ref_checkout = target.clone();
+                    let t_ref_track = target.clone();
+                    let t_ref_del_b = target.clone();
+                    let t_ref_del_tag = target.clone();
+                    let t_ref_copy = target.clone();
+                    let t_sm_init = target.clone();
+                    let t_wt_lock = target.clone();
+                    let t_worktree_here = target.clone();
+                    let worktree_locked = worktrees.iter().any(|w| w.name == *target && w.locked.is_some());
+                    let t_sm_update = target.clone();
+                    let t_sm_sync = target.clone();
+                    let t_reword = target.clone();
+                    let t_author = target.clone();
+                    let t_fixup = target.clone();
+                    let t_split = target.clone();
 
                     let p_cherry = repo_path.read().clone();
                     let p_revert = repo_path.read().clone();
-                    let p_discard = repo_path.read().clone();
                     let p_checkout = repo_path.read().clone();
-                    let p_reset = repo_path.read().clone();
                     let p_ignore = repo_path.read().clone();
                     let p_checkout_b = repo_path.read().clone();
-                    let p_del_b = repo_path.read().clone();
+                    let p_solo = repo_path.read().clone();
                     let p_squash = repo_path.read().clone();
                     let p_patch = repo_path.read().clone();
                     let p_info = repo_path.read().clone();
                     let p_stage = repo_path.read().clone();
                     let p_unstage = repo_path.read().clone();
+                    let p_ref_checkout = repo_path.read().clone();
+                    let p_ref_track = repo_path.read().clone();
+                    let p_ref_del_tag = repo_path.read().clone();
+                    let p_sm_init = repo_path.read().clone();
+                    let p_wt_lock = repo_path.read().clone();
+                    let p_sm_update = repo_path.read().clone();
+                    let p_sm_sync = repo_path.read().clone();
+                    let p_reword = repo_path.read().clone();
+                    let p_author = repo_path.read().clone();
+                    let p_fixup = repo_path.read().clone();
+                    let p_split = repo_path.read().clone();
+                    let p_autosquash = repo_path.read().clone();
                     
                     let menu_type = m_type.clone();
 
@@ -639,6 +1337,10 @@ pub fn app() -> Element {
                                     div { style: "padding: 4px 12px; font-weight: bold; color: var(--text-sub); font-size: 0.8em;", "QUICK ACTIONS (HEAD)" }
                                     div { class: "dropdown-item", 
                                         onclick: move |_| { 
+                                            if let Ok((_, _, m, _, _)) = GitHandler::get_commit_details(&p_reword, &t_reword) {
+                                                reword_input.set(m.trim_end().to_string());
+                                            }
+                                            rewrite_target.set(t_reword.clone());
                                             show_reword_modal.set(true);
                                             context_menu_pos.set(None); 
                                         }, 
@@ -646,11 +1348,51 @@ pub fn app() -> Element {
                                     }
                                     div { class: "dropdown-item", 
                                         onclick: move |_| { 
+                                            if let Ok((a, _, _, _, _)) = GitHandler::get_commit_details(&p_author, &t_author) {
+                                                if let Some((name, email)) = a.split_once(" <") {
+                                                    author_name_input.set(name.to_string());
+                                                    author_email_input.set(email.trim_end_matches('>').to_string());
+                                                }
+                                            }
+                                            rewrite_target.set(t_author.clone());
                                             show_author_modal.set(true);
                                             context_menu_pos.set(None); 
                                         }, 
                                         "{i18n.translate(\"menu-author\")}" 
                                     }
+                                    div { class: "dropdown-item", 
+                                        onclick: move |_| { 
+                                            match GitHandler::get_split_files(&p_split, &t_split) {
+                                                Ok(files) => {
+                                                    let rest = GitHandler::get_commit_details(&p_split, &t_split).map(|d| d.2.trim_end().to_string()).unwrap_or_default();
+                                                    split_files.set(files);
+                                                    split_parts.write().clear();
+                                                    split_selection.write().clear();
+                                                    split_part_msg.set("".to_string());
+                                                    split_rest_msg.set(rest);
+                                                    rewrite_target.set(t_split.clone());
+                                                    show_split_modal.set(true);
+                                                }
+                                                Err(e) => status_msg.set(format!("Error: {}", e)),
+                                            }
+                                            context_menu_pos.set(None); 
+                                        }, 
+                                        "{i18n.translate(\"menu-split\")}" 
+                                    }
+                                    div { class: "dropdown-item", 
+                                        onclick: move |_| { 
+                                            match GitHandler::create_fixup_commit(&p_fixup, &t_fixup, sign_commit, !*bypass_hooks.read()) {
+                                                Ok(_) => status_msg.set("Fixup commit created".to_string()),
+                                                Err(e) => {
+                                                    if e == "err-hook-failed" { show_hook_log.set(true); }
+                                                    status_msg.set(format!("Error: {}", i18n_service.read().translate(&e)));
+                                                }
+                                            }
+                                            context_menu_pos.set(None); 
+                                            let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); 
+                                        }, 
+                                        "{i18n.translate(\"menu-fixup\")}" 
+                                    }
                                     div { class: "dropdown-item", 
                                         onclick: move |_| { 
                                             let _ = GitHandler::squash_parent(&p_squash); 
@@ -659,12 +1401,24 @@ pub fn app() -> Element {
                                         }, 
                                         "{i18n.translate(\"menu-squash\")}" 
                                     }
+                                    div { class: "dropdown-item", 
+                                        onclick: move |_| { 
+                                            match GitHandler::autosquash(&p_autosquash) {
+                                                Ok(n) => status_msg.set(format!("Autosquash folded {} commit(s)", n)),
+                                                Err(e) => status_msg.set(format!("Error: {}", i18n_service.read().translate(&e))),
+                                            }
+                                            context_menu_pos.set(None); 
+                                            let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); 
+                                        }, 
+                                        "{i18n.translate(\"menu-autosquash\")}" 
+                                    }
                                     div { class: "separator" }
                                     div { class: "dropdown-item", onclick: move |_| { let _ = GitHandler::checkout_commit(&p_checkout, &t5_checkout); context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-checkout\")}" }
                                     div { class: "dropdown-item", onclick: move |_| { let _ = GitHandler::cherry_pick(&p_cherry, &t1); context_menu_pos.set(None); }, "{i18n.translate(\"menu-cherry\")}" }
                                     div { class: "dropdown-item", onclick: move |_| { let _ = GitHandler::revert_commit(&p_revert, &t2); context_menu_pos.set(None); }, "{i18n.translate(\"menu-revert\")}" }
                                     div { class: "dropdown-item", onclick: move |_| { let _ = GitHandler::save_patch(&p_patch, &t_patch); context_menu_pos.set(None); }, "{i18n.translate(\"menu-patch\")}" }
-                                    div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { let _ = GitHandler::reset_hard(&p_reset, &t6_reset); context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-reset\")}" }
+                                    div { class: "dropdown-item", onclick: move |_| { worktree_rev_input.set(t_worktree_here.clone()); worktree_name_input.set("".to_string()); show_worktree_modal.set(true); context_menu_pos.set(None); }, "{i18n.translate(\"menu-worktree-here\")}" }
+                                    div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { context_menu_pos.set(None); request_action("reset", t6_reset.clone()); }, "{i18n.translate(\"menu-reset\")}" }
                                     div { class: "separator" }
                                     div { class: "dropdown-item", onclick: move |_| { let _ = Command::new("powershell").arg("-Command").arg(format!("Set-Clipboard -Value '{}'", t3)).spawn(); context_menu_pos.set(None); }, "{i18n.translate(\"menu-copy\")}" }
                                     div { class: "dropdown-item", 
@@ -703,13 +1457,50 @@ pub fn app() -> Element {
                                     }
                                     div { class: "dropdown-item", onclick: move |_| { let _ = GitHandler::add_to_gitignore(&p_ignore, &t8_ignore); context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-ignore\")}" }
                                     div { class: "separator" }
-                                    div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { let _ = GitHandler::discard_changes(&p_discard, &t4_discard); context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-discard\")}" }
+                                    div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { context_menu_pos.set(None); request_action("discard", t4_discard.clone()); }, "{i18n.translate(\"menu-discard\")}" }
                                 } 
                             } else if menu_type == "branch" { 
                                 div {
                                     div { class: "dropdown-item", onclick: move |_| { let _ = GitHandler::checkout_branch(&p_checkout_b, &t9_checkout_b); context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-checkout-branch\")}" }
+                                    div { class: "dropdown-item", onclick: move |_| { reflog_ref.set(format!("refs/heads/{}", t_reflog)); show_reflog.set(true); context_menu_pos.set(None); }, "{i18n.translate(\"menu-show-reflog\")}" }
+                                    div { class: "dropdown-item", onclick: move |_| { let full_ref = format!("refs/heads/{}", t_solo); let all_refs = all_ref_names(&p_solo); let _ = update_repo_settings(&p_solo, |s| { s.show_all_refs = true; s.hide_remotes = false; s.hidden_refs = all_refs.into_iter().filter(|r| *r != full_ref).collect(); }); context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-solo-branch\")}" }
                                     div { class: "separator" }
-                                    div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { let _ = GitHandler::delete_branch(&p_del_b, &t10_del_b); context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-del-branch\")}" }
+                                    div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { context_menu_pos.set(None); request_action("delete-branch", t10_del_b.clone()); }, "{i18n.translate(\"menu-del-branch\")}" }
+                                }
+                            } else if menu_type == "worktree" {
+                                div {
+                                    div { style: "padding: 4px 12px; font-weight: bold; color: var(--text-sub); font-size: 0.8em;", "{target}" }
+                                    div { class: "dropdown-item", onclick: move |_| { let res = if worktree_locked { GitHandler::unlock_worktree(&p_wt_lock, &t_wt_lock) } else { GitHandler::lock_worktree(&p_wt_lock, &t_wt_lock, None) }; if let Err(e) = res { status_msg.set(format!("Worktree Error: {}", e)); } context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); },
+                                        if worktree_locked { "{i18n.translate(\"menu-worktree-unlock\")}" } else { "{i18n.translate(\"menu-worktree-lock\")}" }
+                                    }
+                                }
+                            } else if menu_type == "submodule" {
+                                div {
+                                    div { style: "padding: 4px 12px; font-weight: bold; color: var(--text-sub); font-size: 0.8em;", "{target}" }
+                                    div { class: "dropdown-item", onclick: move |_| { match GitHandler::submodule_init(&p_sm_init, &t_sm_init) { Ok(_) => status_msg.set("Submodule initialized".to_string()), Err(e) => status_msg.set(format!("Submodule Error: {}", e)), } context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-submodule-init\")}" }
+                                    div { class: "dropdown-item", onclick: move |_| { let p = p_sm_update.clone(); let name = t_sm_update.clone(); context_menu_pos.set(None); spawn(async move { status_msg.set("Updating submodule...".to_string()); match GitHandler::submodule_update(&p, &name) { Ok(_) => status_msg.set("Submodule updated".to_string()), Err(e) => status_msg.set(format!("Submodule Error: {}", e)), } let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }); }, "{i18n.translate(\"menu-submodule-update\")}" }
+                                    div { class: "dropdown-item", onclick: move |_| { match GitHandler::submodule_sync(&p_sm_sync, &t_sm_sync) { Ok(_) => status_msg.set("Submodule synced".to_string()), Err(e) => status_msg.set(format!("Submodule Error: {}", e)), } context_menu_pos.set(None); }, "{i18n.translate(\"menu-submodule-sync\")}" }
+                                }
+                            } else if menu_type.starts_with("ref_") {
+                                div {
+                                    div { style: "padding: 4px 12px; font-weight: bold; color: var(--text-sub); font-size: 0.8em;", "{target}" }
+                                    if menu_type == "ref_head" {
+                                        div { class: "dropdown-item", onclick: move |_| { show_branch_modal.set(true); context_menu_pos.set(None); }, "{i18n.translate(\"menu-ref-branch-here\")}" }
+                                    } else {
+                                        div { class: "dropdown-item", onclick: move |_| { match GitHandler::checkout_branch(&p_ref_checkout, &t_ref_checkout) { Ok(_) => {}, Err(e) => status_msg.set(format!("Checkout Error: {}", e)), } context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-checkout\")}" }
+                                    }
+                                    if menu_type == "ref_remote" {
+                                        div { class: "dropdown-item", onclick: move |_| { match GitHandler::track_remote_branch(&p_ref_track, &t_ref_track) { Ok(local) => { let _ = GitHandler::checkout_branch(&p_ref_track, &local); status_msg.set("Branch created".to_string()); }, Err(e) => status_msg.set(format!("Error: {}", e)), } context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-ref-track\")}" }
+                                    }
+                                    div { class: "dropdown-item", onclick: move |_| { let _ = Command::new("powershell").arg("-Command").arg(format!("Set-Clipboard -Value '{}'", t_ref_copy)).spawn(); context_menu_pos.set(None); }, "{i18n.translate(\"menu-ref-copy\")}" }
+                                    if menu_type == "ref_local" {
+                                        div { class: "separator" }
+                                        div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { context_menu_pos.set(None); request_action("delete-branch", t_ref_del_b.clone()); }, "{i18n.translate(\"menu-del-branch\")}" }
+                                    }
+                                    if menu_type == "ref_tag" {
+                                        div { class: "separator" }
+                                        div { class: "dropdown-item", style: "color: var(--accent-secondary);", onclick: move |_| { match GitHandler::delete_tag(&p_ref_del_tag, &t_ref_del_tag) { Ok(_) => {}, Err(e) => status_msg.set(format!("Error: {}", e)), } context_menu_pos.set(None); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"menu-del-tag\")}" }
+                                    }
                                 }
                             }
                         }
@@ -729,16 +1520,18 @@ pub fn app() -> Element {
                             value: "{reword_input}",
                             oninput: move |e| reword_input.set(e.value())
                         }
+                        div { class: if rewrite_blocked { "amend-warning" } else { "rewrite-note" }, "{rewrite_note}" }
                         div {
                             style: "display: flex; justify-content: flex-end; gap: 10px;",
                             button { class: "toolbar-btn", onclick: move |_| show_reword_modal.set(false), "{i18n.translate(\"modal-cancel\")}" }
                             button { 
                                 class: "btn-primary",
+                                disabled: rewrite_blocked,
                                 onclick: move |_| {
                                     let p = repo_path.read().clone();
                                     let msg = reword_input.read().clone();
-                                    match GitHandler::amend_head(&p, Some(&msg), None) {
-                                        Ok(_) => status_msg.set("Reword successful".to_string()),
+                                    match GitHandler::rewrite_commit(&p, &rewrite_target.read(), Some(&msg), None) {
+                                        Ok(n) => status_msg.set(format!("Reword successful, {} commit(s) rewritten", n)),
                                         Err(e) => status_msg.set(format!("Error: {}", e)),
                                     }
                                     show_reword_modal.set(false);
@@ -767,18 +1560,20 @@ pub fn app() -> Element {
                             style: "width: 100%; margin: 8px 0;", placeholder: "Email",
                             value: "{author_email_input}", oninput: move |e| author_email_input.set(e.value())
                         }
+                        div { class: if rewrite_blocked { "amend-warning" } else { "rewrite-note" }, "{rewrite_note}" }
                         div {
                             style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
                             button { class: "toolbar-btn", onclick: move |_| show_author_modal.set(false), "{i18n.translate(\"modal-cancel\")}" }
                             button { 
                                 class: "btn-primary",
+                                disabled: rewrite_blocked,
                                 onclick: move |_| {
                                     let p = repo_path.read().clone();
                                     let name = author_name_input.read().clone();
                                     let email = author_email_input.read().clone();
                                     if !name.is_empty() && !email.is_empty() {
-                                        match GitHandler::amend_head(&p, None, Some((&name, &email))) {
-                                            Ok(_) => status_msg.set("Author changed".to_string()),
+                                        match GitHandler::rewrite_commit(&p, &rewrite_target.read(), None, Some((&name, &email))) {
+                                            Ok(n) => status_msg.set(format!("Author changed, {} commit(s) rewritten", n)),
                                             Err(e) => status_msg.set(format!("Error: {}", e)),
                                         }
                                         show_author_modal.set(false);
@@ -792,6 +1587,108 @@ pub fn app() -> Element {
                 }
             }
 
+            if *show_split_modal.read() {
+                div {
+                    style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
+                    div {
+                        style: "background: var(--bg-surface); padding: 25px; border-radius: 12px; width: 760px; max-height: 85vh; display: flex; flex-direction: column; box-shadow: 0 10px 30px rgba(0,0,0,0.5); border: 1px solid var(--border-color); color: var(--text-main);",
+                        h3 { style: "margin-top: 0;", "{i18n.translate(\"modal-split-title\")}" }
+                        div { class: "rewrite-note", "{i18n.translate(\"split-hint\")}" }
+                        div { class: if rewrite_blocked { "amend-warning" } else { "rewrite-note" }, "{rewrite_note}" }
+                        div { class: "split-files",
+                            for (path, hunks) in split_view {
+                                {
+                                    let open: Vec<(String, usize)> = hunks.iter().filter(|h| h.2.is_none()).map(|h| (path.clone(), h.0)).collect();
+                                    let file_checked = !open.is_empty() && hunks.iter().filter(|h| h.2.is_none()).all(|h| h.3);
+                                    rsx! {
+                                        div { class: "split-file",
+                                            label { class: "split-file-header",
+                                                input { r#type: "checkbox", checked: file_checked, disabled: open.is_empty(),
+                                                    onchange: move |evt| {
+                                                        let mut selection = split_selection.write();
+                                                        selection.retain(|key| !open.contains(key));
+                                                        if evt.checked() { selection.extend(open.iter().cloned()); }
+                                                    }
+                                                }
+                                                "{path}"
+                                            }
+                                            for (idx, text, part, checked) in hunks {
+                                                {
+                                                    let key = (path.clone(), idx);
+                                                    rsx! {
+                                                        div { class: if part.is_some() { "split-hunk taken" } else { "split-hunk" },
+                                                            if let Some(n) = part {
+                                                                span { class: "split-part-badge", "#{n}" }
+                                                            } else {
+                                                                input { r#type: "checkbox", checked: checked,
+                                                                    onchange: move |evt| {
+                                                                        let mut selection = split_selection.write();
+                                                                        selection.retain(|k| *k != key);
+                                                                        if evt.checked() { selection.push(key.clone()); }
+                                                                    }
+                                                                }
+                                                            }
+                                                            pre { "{text}" }
+                                                        }
+                                                    }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        for (i, label) in split_part_labels.into_iter().enumerate() {
+                            div { class: "split-part",
+                                span { "{label}" }
+                                span { class: "chip-remove", onclick: move |_| { split_parts.write().remove(i); }, "×" }
+                            }
+                        }
+                        div { style: "display: flex; gap: 10px; margin: 10px 0;",
+                            input { class: "input-modern", style: "flex: 1;",
+                                placeholder: "{i18n.translate(\"split-part-message\")}",
+                                value: "{split_part_msg}", oninput: move |e| split_part_msg.set(e.value())
+                            }
+                            button { class: "toolbar-btn",
+                                disabled: split_selection.read().is_empty() || split_part_msg.read().trim().is_empty(),
+                                onclick: move |_| {
+                                    let hunks: Vec<(String, usize)> = split_selection.write().drain(..).collect();
+                                    let msg = split_part_msg.read().clone();
+                                    split_parts.write().push((msg, hunks));
+                                    split_part_msg.set("".to_string());
+                                },
+                                "{i18n.translate(\"split-add-part\")}"
+                            }
+                        }
+                        div { class: "rewrite-note", "{i18n.translate(\"split-rest\")}" }
+                        textarea { class: "input-modern", style: "width: 100%; height: 60px; padding: 5px;",
+                            value: "{split_rest_msg}", oninput: move |e| split_rest_msg.set(e.value())
+                        }
+                        div {
+                            style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
+                            button { class: "toolbar-btn", onclick: move |_| show_split_modal.set(false), "{i18n.translate(\"modal-cancel\")}" }
+                            button { 
+                                class: "btn-primary",
+                                disabled: rewrite_blocked || split_parts.read().is_empty(),
+                                onclick: move |_| {
+                                    let p = repo_path.read().clone();
+                                    let rest = split_rest_msg.read().clone();
+                                    match GitHandler::split_commit(&p, &rewrite_target.read(), &split_parts.read(), &rest) {
+                                        Ok(n) => {
+                                            status_msg.set(format!("Split into {} commits", n));
+                                            show_split_modal.set(false);
+                                        }
+                                        Err(e) => status_msg.set(format!("Error: {}", i18n_service.read().translate(&e))),
+                                    }
+                                    let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
+                                }, 
+                                "{i18n.translate(\"split-confirm\")}" 
+                            }
+                        }
+                    }
+                }
+            }
+
             if *show_clone_modal.read() {
                 div {
                     style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
@@ -803,6 +1700,10 @@ pub fn app() -> Element {
                             style: "width: 100%; margin: 8px 0;", placeholder: "https://github.com/user/repo.git",
                             value: "{clone_url_input}", oninput: move |e| clone_url_input.set(e.value())
                         }
+                        label { class: "commit-option",
+                            input { r#type: "checkbox", checked: *clone_recursive.read(), onchange: move |e| clone_recursive.set(e.checked()) }
+                            "{i18n.translate(\"modal-clone-recursive\")}"
+                        }
                         div {
                             style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
                             button { class: "toolbar-btn", onclick: move |_| show_clone_modal.set(false), "{i18n.translate(\"modal-cancel\")}" }
@@ -813,7 +1714,7 @@ pub fn app() -> Element {
                                         let new_path = path.display().to_string();
                                         let url = clone_url_input.read().clone();
                                         if !url.is_empty() {
-                                            match GitHandler::clone(&url, &new_path) {
+                                            match GitHandler::clone(&url, &new_path, *clone_recursive.read()) {
                                                 Ok(_) => {
                                                     status_msg.set("Clone successful".to_string()); 
                                                         let _ = save_credentials(&git_user.read(), &git_token.read(), &new_path);
@@ -903,6 +1804,392 @@ pub fn app() -> Element {
                 }
             }
 
+            if *show_worktree_modal.read() {
+                div {
+                    style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
+                    div {
+                        style: "background: var(--bg-surface); padding: 25px; border-radius: 12px; width: 420px; box-shadow: 0 10px 30px rgba(0,0,0,0.5); border: 1px solid var(--border-color); color: var(--text-main);",
+                        h3 { style: "margin-top: 0;", "{i18n.translate(\"modal-worktree-title\")}" }
+                        input {
+                            class: "input-modern",
+                            style: "width: 100%; margin: 8px 0;", placeholder: "{i18n.translate(\"modal-worktree-name\")}",
+                            value: "{worktree_name_input}", oninput: move |e| worktree_name_input.set(e.value())
+                        }
+                        input {
+                            class: "input-modern",
+                            style: "width: 100%; margin: 8px 0;", placeholder: "{i18n.translate(\"modal-worktree-rev\")}",
+                            value: "{worktree_rev_input}", oninput: move |e| worktree_rev_input.set(e.value())
+                        }
+                        div {
+                            style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
+                            button { class: "toolbar-btn", onclick: move |_| show_worktree_modal.set(false), "{i18n.translate(\"modal-cancel\")}" }
+                            button {
+                                class: "btn-primary",
+                                onclick: move |_| {
+                                    let p = repo_path.read().clone();
+                                    let name = worktree_name_input.read().trim().to_string();
+                                    let rev = worktree_rev_input.read().trim().to_string();
+                                    if !name.is_empty() && !rev.is_empty() {
+                                        if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                                            let target = folder.join(&name).display().to_string();
+                                            match GitHandler::create_worktree(&p, &rev, &target) {
+                                                Ok(_) => status_msg.set("Worktree created".to_string()),
+                                                Err(e) => status_msg.set(format!("Worktree Error: {}", i18n_service.read().translate(&e))),
+                                            }
+                                            show_worktree_modal.set(false);
+                                            let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
+                                        }
+                                    }
+                                },
+                                "{i18n.translate(\"modal-create\")}"
+                            }
+                        }
+                    }
+                }
+            }
+
+            if *show_reflog.read() {
+                div {
+                    style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
+                    div {
+                        style: "background: var(--bg-surface); padding: 25px; border-radius: 12px; width: 720px; max-height: 75vh; display: flex; flex-direction: column; box-shadow: 0 10px 30px rgba(0,0,0,0.5); border: 1px solid var(--border-color); color: var(--text-main);",
+                        div { style: "display: flex; align-items: center; gap: 10px; margin-bottom: 10px;",
+                            h3 { style: "margin: 0; flex: 1;", "{i18n.translate(\"modal-reflog-title\")}" }
+                            select {
+                                class: "input-modern",
+                                value: "{reflog_ref}",
+                                onchange: move |e| reflog_ref.set(e.value()),
+                                option { value: "HEAD", "HEAD" }
+                                for branch in branches.iter() {
+                                    option { value: "refs/heads/{branch}", "{branch}" }
+                                }
+                            }
+                        }
+                        div { style: "flex: 1; overflow-y: auto; min-height: 0;",
+                            if reflog_entries.is_empty() {
+                                div { style: "color: var(--text-sub); font-style: italic; padding: 10px 0;", "{i18n.translate(\"reflog-empty\")}" }
+                            }
+                            table {
+                                tbody {
+                                    for entry in reflog_entries.iter() {
+                                        {
+                                            let sha_click = entry.new_sha.clone();
+                                            let short_sha = entry.new_sha[..7].to_string();
+                                            rsx! {
+                                                tr {
+                                                    style: "cursor: pointer;",
+                                                    onclick: move |_| { selected_commit.set(Some(sha_click.clone())); view_mode.set("history".to_string()); selected_file.set(None); show_reflog.set(false); },
+                                                    td { class: "reflog-selector", "{entry.selector}" }
+                                                    td { class: "reflog-sha", "{short_sha}" }
+                                                    td { "{entry.message}" }
+                                                    td { style: "white-space: nowrap; color: var(--text-sub);", "{entry.time}" }
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        div {
+                            style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
+                            button { class: "toolbar-btn", onclick: move |_| show_reflog.set(false), "{i18n.translate(\"modal-close\")}" }
+                        }
+                    }
+                }
+            }
+
+            if *show_snapshots.read() {
+                div {
+                    style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
+                    div {
+                        style: "background: var(--bg-surface); padding: 25px; border-radius: 12px; width: 720px; max-height: 75vh; display: flex; flex-direction: column; box-shadow: 0 10px 30px rgba(0,0,0,0.5); border: 1px solid var(--border-color); color: var(--text-main);",
+                        h3 { style: "margin-top: 0;", "{i18n.translate(\"modal-snapshots-title\")}" }
+                        div { style: "font-size: 0.85em; color: var(--text-sub); margin-bottom: 10px;", "{i18n.translate(\"modal-snapshots-hint\")}" }
+                        div { style: "flex: 1; overflow-y: auto; min-height: 0;",
+                            if snapshots.is_empty() {
+                                div { style: "color: var(--text-sub); font-style: italic; padding: 10px 0;", "{i18n.translate(\"snapshots-empty\")}" }
+                            }
+                            for snapshot in snapshots.iter() {
+                                {
+                                    let ref_restore = snapshot.refname.clone();
+                                    let ref_delete = snapshot.refname.clone();
+                                    let summary = snapshot.message.lines().next().unwrap_or_default().to_string();
+                                    let file_list = snapshot.files.join(", ");
+                                    rsx! {
+                                        div { class: "snapshot-item",
+                                            div { style: "flex: 1; min-width: 0;",
+                                                div { style: "font-weight: 600;", "{summary}" }
+                                                div { class: "reflog-sha", style: "font-size: 0.8em;", "{snapshot.time} \u{00b7} {snapshot.files.len()} files" }
+                                                div { class: "snapshot-files", title: "{file_list}", "{file_list}" }
+                                            }
+                                            button {
+                                                class: "btn-primary",
+                                                onclick: move |_| {
+                                                    let p = repo_path.read().clone();
+                                                    match GitHandler::restore_snapshot(&p, &ref_restore) {
+                                                        Ok(_) => status_msg.set("Snapshot restored".to_string()),
+                                                        Err(e) => status_msg.set(format!("Restore Error: {}", e)),
+                                                    }
+                                                    show_snapshots.set(false);
+                                                    let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
+                                                },
+                                                "{i18n.translate(\"snapshot-restore\")}"
+                                            }
+                                            button {
+                                                class: "toolbar-btn",
+                                                onclick: move |_| {
+                                                    let p = repo_path.read().clone();
+                                                    if let Err(e) = GitHandler::delete_snapshot(&p, &ref_delete) { status_msg.set(format!("Error: {}", e)); }
+                                                    let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
+                                                },
+                                                "{i18n.translate(\"snapshot-delete\")}"
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        div { style: "display: flex; align-items: center; gap: 8px; margin-top: 15px; font-size: 0.85em; color: var(--text-sub);",
+                            "{i18n.translate(\"snapshots-keep\")}"
+                            input { class: "input-modern", style: "width: 60px; padding: 4px;", r#type: "number", min: "1", value: "{snapshot_keep_input}", oninput: move |e| snapshot_keep_input.set(e.value()) }
+                            "{i18n.translate(\"snapshots-days\")}"
+                            input { class: "input-modern", style: "width: 60px; padding: 4px;", r#type: "number", min: "0", value: "{snapshot_days_input}", oninput: move |e| snapshot_days_input.set(e.value()) }
+                            div { style: "flex: 1;" }
+                            button { class: "toolbar-btn", onclick: move |_| show_snapshots.set(false), "{i18n.translate(\"modal-close\")}" }
+                            button {
+                                class: "btn-primary",
+                                onclick: move |_| {
+                                    let p = repo_path.read().clone();
+                                    let keep = snapshot_keep_input.read().trim().parse().unwrap_or(DEFAULT_KEEP);
+                                    let days = snapshot_days_input.read().trim().parse().unwrap_or(DEFAULT_EXPIRY_DAYS);
+                                    match GitHandler::set_snapshot_retention(&p, keep, days) {
+                                        Ok(_) => status_msg.set("Retention saved".to_string()),
+                                        Err(e) => status_msg.set(format!("Error: {}", e)),
+                                    }
+                                    let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
+                                },
+                                "{i18n.translate(\"modal-save\")}"
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some(preview) = undo_preview {
+                div {
+                    style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
+                    div {
+                        style: "background: var(--bg-surface); padding: 25px; border-radius: 12px; width: 520px; max-height: 75vh; display: flex; flex-direction: column; box-shadow: 0 10px 30px rgba(0,0,0,0.5); border: 1px solid var(--border-color); color: var(--text-main);",
+                        h3 { style: "margin-top: 0;", "{i18n.translate(\"modal-undo-title\")}" }
+                        match preview {
+                            Ok(p) => {
+                                let op_label = i18n.translate(&format!("op-{}", p.operation));
+                                let short_current = p.current[..7].to_string();
+                                let short_target = p.target[..7].to_string();
+                                rsx! {
+                                    div { style: "font-size: 0.9em; margin-bottom: 10px;",
+                                        "{op_label} \u{2014} "
+                                        span { class: "reflog-sha", "{p.refname}" }
+                                        ": {short_current} \u{2192} {short_target}"
+                                    }
+                                    div { style: "flex: 1; overflow-y: auto; min-height: 0; font-size: 0.85em;",
+                                        if !p.restored.is_empty() {
+                                            div { class: "undo-list-title", "{i18n.translate(\"undo-restored\")}" }
+                                            for (sha, summary) in p.restored.iter() {
+                                                div { class: "undo-commit restored", span { class: "reflog-sha", "{&sha[..7]}" } " {summary}" }
+                                            }
+                                        }
+                                        if !p.removed.is_empty() {
+                                            div { class: "undo-list-title", "{i18n.translate(\"undo-removed\")}" }
+                                            for (sha, summary) in p.removed.iter() {
+                                                div { class: "undo-commit removed", span { class: "reflog-sha", "{&sha[..7]}" } " {summary}" }
+                                            }
+                                        }
+                                    }
+                                    div {
+                                        style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
+                                        button { class: "toolbar-btn", onclick: move |_| show_undo_modal.set(false), "{i18n.translate(\"modal-cancel\")}" }
+                                        button {
+                                            class: "btn-primary",
+                                            onclick: move |_| {
+                                                let path = repo_path.read().clone();
+                                                match GitHandler::undo_last_operation(&path) {
+                                                    Ok(_) => status_msg.set("Undo successful".to_string()),
+                                                    Err(e) => status_msg.set(format!("Undo Error: {}", i18n_service.read().translate(&e))),
+                                                }
+                                                show_undo_modal.set(false);
+                                                let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
+                                            },
+                                            "{i18n.translate(\"modal-undo\")}"
+                                        }
+                                    }
+                                }
+                            }
+                            Err(e) => rsx! {
+                                div { style: "color: var(--text-sub); font-size: 0.9em;", "{i18n.translate(&e)}" }
+                                div {
+                                    style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
+                                    button { class: "toolbar-btn", onclick: move |_| show_undo_modal.set(false), "{i18n.translate(\"modal-close\")}" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            if let Some((kind, target, preview, title, description, action_label)) = action_preview {
+                div {
+                    style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
+                    div {
+                        style: "background: var(--bg-surface); padding: 25px; border-radius: 12px; width: 520px; max-height: 75vh; display: flex; flex-direction: column; box-shadow: 0 10px 30px rgba(0,0,0,0.5); border: 1px solid var(--border-color); color: var(--text-main);",
+                        h3 { style: "margin-top: 0;", "{title}" }
+                        div { style: "font-size: 0.9em; margin-bottom: 10px;",
+                            "{description} "
+                            if !target.is_empty() { span { class: "reflog-sha", "{target}" } }
+                        }
+                        match preview {
+                            Ok(p) => rsx! {
+                                div { style: "flex: 1; overflow-y: auto; min-height: 0; font-size: 0.85em;",
+                                    if !p.files.is_empty() {
+                                        div { class: "undo-list-title", "{i18n.translate(\"confirm-files\")}" }
+                                        for file in p.files.iter() {
+                                            div { class: "confirm-file", "{file}" }
+                                        }
+                                    }
+                                    if !p.commits.is_empty() {
+                                        div { class: "undo-list-title",
+                                            if kind.starts_with("force-push") { "{i18n.translate(\"confirm-remote-commits\")}" } else { "{i18n.translate(\"confirm-lost-commits\")}" }
+                                        }
+                                        for (sha, summary) in p.commits.iter() {
+                                            div { class: "undo-commit removed", span { class: "reflog-sha", "{&sha[..7]}" } " {summary}" }
+                                        }
+                                    }
+                                    if p.files.is_empty() && p.commits.is_empty() {
+                                        div { style: "color: var(--text-sub);", "{i18n.translate(\"confirm-nothing-lost\")}" }
+                                    }
+                                    if kind == "force-push-lease" {
+                                        div { style: "color: var(--text-sub); margin-top: 8px;", "{i18n.translate(\"confirm-force-push-lease-hint\")}" }
+                                    } else if kind == "force-push" {
+                                        div { style: "color: var(--text-sub); margin-top: 8px;", "{i18n.translate(\"confirm-force-push-fetch-hint\")}" }
+                                    }
+                                }
+                            },
+                            Err(e) => rsx! {
+                                div { style: "color: var(--text-sub); font-size: 0.9em;", "{i18n.translate(&e)}" }
+                            }
+                        }
+                        label { class: "commit-option", style: "margin-top: 12px;",
+                            input { r#type: "checkbox", checked: *confirm_dont_ask.read(), onchange: move |evt| confirm_dont_ask.set(evt.checked()) }
+                            "{i18n.translate(\"confirm-dont-ask\")}"
+                        }
+                        div {
+                            style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
+                            button { class: "toolbar-btn", onclick: move |_| { pending_action.set(None); pending_preview.set(None); }, "{i18n.translate(\"modal-cancel\")}" }
+                            button {
+                                class: "btn-primary",
+                                onclick: move |_| {
+                                    let Some((kind, target)) = pending_action.read().clone() else { return };
+                                    pending_action.set(None);
+                                    pending_preview.set(None);
+                                    if *confirm_dont_ask.read() {
+                                        let mut settings = load_app_settings();
+                                        settings.skip_confirmations.push(kind.clone());
+                                        if let Err(e) = save_app_settings(&settings) {
+                                            status_msg.set(e);
+                                        }
+                                    }
+                                    spawn(async move {
+                                        let p = repo_path.read().clone(); let u = git_user.read().clone(); let t = git_token.read().clone();
+                                        let msg = run_destructive_action(&p, &kind, &target, &u, &t, !*bypass_hooks.read(), &i18n_service.read());
+                                        status_msg.set(msg);
+                                        let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
+                                    });
+                                },
+                                "{action_label}"
+                            }
+                        }
+                    }
+                }
+            }
+
+            if *show_hook_log.read() {
+                div {
+                    style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
+                    div {
+                        style: "background: var(--bg-surface); padding: 25px; border-radius: 12px; width: 640px; max-height: 75vh; display: flex; flex-direction: column; box-shadow: 0 10px 30px rgba(0,0,0,0.5); border: 1px solid var(--border-color); color: var(--text-main);",
+                        h3 { style: "margin-top: 0;", "{i18n.translate(\"modal-hook-log-title\")}" }
+                        div { style: "flex: 1; overflow-y: auto; min-height: 0; font-size: 0.85em;",
+                            if hook_log.is_empty() {
+                                div { style: "color: var(--text-sub);", "{i18n.translate(\"hook-log-empty\")}" }
+                            }
+                            for run in hook_log.iter() {
+                                div { class: if run.success { "hook-run" } else { "hook-run failed" },
+                                    div { class: "hook-run-header",
+                                        span { class: "reflog-sha", "{run.time}" }
+                                        span { style: "font-weight: 600;", "{run.hook}" }
+                                        span { style: "margin-left: auto;", if run.success { "{i18n.translate(\"hook-passed\")}" } else { "{i18n.translate(\"hook-failed\")}" } }
+                                    }
+                                    if !run.output.is_empty() {
+                                        pre { class: "hook-output", "{run.output}" }
+                                    }
+                                }
+                            }
+                        }
+                        div {
+                            style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
+                            button { class: "toolbar-btn", onclick: move |_| { GitHandler::clear_hook_log(); let next = *refresh_trigger.read() + 1; refresh_trigger.set(next); }, "{i18n.translate(\"hook-log-clear\")}" }
+                            button { class: "btn-primary", onclick: move |_| show_hook_log.set(false), "{i18n.translate(\"modal-close\")}" }
+                        }
+                    }
+                }
+            }
+
+            if *show_export_modal.read() {
+                div {
+                    style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
+                    div {
+                        style: "background: var(--bg-surface); padding: 25px; border-radius: 12px; width: 420px; box-shadow: 0 10px 30px rgba(0,0,0,0.5); border: 1px solid var(--border-color); color: var(--text-main);",
+                        h3 { style: "margin-top: 0;", "{i18n.translate(\"modal-export-title\")}" }
+                        div { style: "font-size: 0.85em; color: var(--text-sub);", "{i18n.translate(\"modal-export-hint\")}" }
+                        input { 
+                            class: "input-modern",
+                            style: "width: 100%; margin: 8px 0;", placeholder: "v1.0..main",
+                            value: "{export_range_input}", oninput: move |e| export_range_input.set(e.value())
+                        }
+                        div {
+                            style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
+                            button { class: "toolbar-btn", onclick: move |_| show_export_modal.set(false), "{i18n.translate(\"modal-cancel\")}" }
+                            button { 
+                                class: "btn-primary",
+                                onclick: move |_| {
+                                    if let Some(out) = rfd::FileDialog::new().add_filter("SVG", &["svg"]).add_filter("PNG", &["png"]).set_file_name("graph.svg").save_file() {
+                                        let p = repo_path.read().clone();
+                                        let range = export_range_input.read().clone();
+                                        let settings = load_repo_settings(&p);
+                                        let options = HistoryOptions {
+                                            sort_mode: history_sort.read().clone(),
+                                            first_parent: *first_parent_only.read(),
+                                            simplify_by_decoration: *simplify_history.read(),
+                                            hidden_refs: settings.hidden_refs.clone(),
+                                            hide_remotes: settings.hide_remotes,
+                                        };
+                                        // Without a range the export matches the history view, a range is exported in full
+                                        let limit = if range.trim().is_empty() { 100 } else { usize::MAX };
+                                        let i18n = i18n_service.read();
+                                        match crate::git::export::export_graph_to_file(&p, &range, limit, settings.show_all_refs, &options, &out) {
+                                            Ok(_) => status_msg.set(i18n.translate_with("export-done", &[("path", &out.display().to_string())])),
+                                            Err(e) => status_msg.set(i18n.translate_with("export-failed", &[("error", &i18n.translate(&e))])),
+                                        }
+                                        show_export_modal.set(false);
+                                    }
+                                }, 
+                                "{i18n.translate(\"modal-export\")}" 
+                            }
+                        }
+                    }
+                }
+            }
+
             if *show_settings_modal.read() {
                 div {
                     style: "position: absolute; top: 0; left: 0; width: 100%; height: 100%; background: rgba(0,0,0,0.5); z-index: 5000; display: flex; align-items: center; justify-content: center;",
@@ -920,6 +2207,48 @@ pub fn app() -> Element {
                             style: "width: 100%; margin: 8px 0;", placeholder: "Token", r#type: "password",
                             value: "{git_token}", oninput: move |e| git_token.set(e.value())
                         }
+                        div { style: "margin: 10px 0; font-weight: bold; font-size: 0.9em;", "{i18n.translate(\"settings-protected-branches\")}" }
+                        input {
+                            class: "input-modern",
+                            style: "width: 100%; margin: 8px 0;", placeholder: "main, master",
+                            value: "{protected_branches_input}", oninput: move |e| protected_branches_input.set(e.value())
+                        }
+                        div { style: "margin: 10px 0; font-weight: bold; font-size: 0.9em;", "{i18n.translate(\"settings-commit-lint\")}" }
+                        label { class: "commit-option", style: "display: flex; margin: 4px 0;",
+                            input { r#type: "checkbox", checked: *conventional_input.read(), onchange: move |evt| conventional_input.set(evt.checked()) }
+                            "{i18n.translate(\"settings-conventional\")}"
+                        }
+                        if *conventional_input.read() {
+                            input {
+                                class: "input-modern",
+                                style: "width: 100%; margin: 4px 0;", placeholder: "{i18n.translate(\"settings-conventional-types\")}", title: "{i18n.translate(\"settings-conventional-types\")}",
+                                value: "{conventional_types_input}", oninput: move |e| conventional_types_input.set(e.value())
+                            }
+                            input {
+                                class: "input-modern",
+                                style: "width: 100%; margin: 4px 0;", placeholder: "{i18n.translate(\"settings-conventional-scopes\")}", title: "{i18n.translate(\"settings-conventional-scopes\")}",
+                                value: "{conventional_scopes_input}", oninput: move |e| conventional_scopes_input.set(e.value())
+                            }
+                        }
+                        label { class: "commit-option", style: "display: flex; margin: 4px 0;",
+                            input { r#type: "checkbox", checked: *block_lint_input.read(), onchange: move |evt| block_lint_input.set(evt.checked()) }
+                            "{i18n.translate(\"settings-block-lint\")}"
+                        }
+                        div { style: "margin: 10px 0; font-weight: bold; font-size: 0.9em;", "{i18n.translate(\"settings-confirmations\")}" }
+                        for kind in CONFIRMED_ACTIONS.iter() {
+                            label { class: "commit-option", style: "display: flex; margin: 4px 0;",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: !skip_confirmations_input.read().iter().any(|k| k == kind),
+                                    onchange: move |evt| {
+                                        let ask = evt.checked();
+                                        skip_confirmations_input.write().retain(|k| k != kind);
+                                        if !ask { skip_confirmations_input.write().push(kind.to_string()); }
+                                    }
+                                }
+                                "{i18n.translate(&confirm_title_key(kind))}"
+                            }
+                        }
                         div {
                             style: "display: flex; justify-content: flex-end; gap: 10px; margin-top: 15px;",
                             button { class: "toolbar-btn", onclick: move |_| show_settings_modal.set(false), "Abbrechen" }
@@ -930,6 +2259,26 @@ pub fn app() -> Element {
                                         Ok(_) => status_msg.set("Anmeldedaten gespeichert!".to_string()),
                                         Err(e) => status_msg.set(format!("Fehler beim Speichern der Anmeldedaten: {}", e)),
                                     }
+                                    let protected = split_list(&protected_branches_input.read());
+                                    let types = split_list(&conventional_types_input.read());
+                                    let scopes = split_list(&conventional_scopes_input.read());
+                                    let conventional = *conventional_input.read();
+                                    let block_lint = *block_lint_input.read();
+                                    if let Err(e) = update_repo_settings(&repo_path.read(), |s| {
+                                        s.protected_branches = protected;
+                                        s.conventional_commits = conventional;
+                                        if !types.is_empty() { s.conventional_types = types; }
+                                        s.conventional_scopes = scopes;
+                                        s.block_on_lint_errors = block_lint;
+                                    }) {
+                                        status_msg.set(e);
+                                    }
+                                    let mut app_settings = load_app_settings();
+                                    app_settings.skip_confirmations = skip_confirmations_input.read().clone();
+                                    if let Err(e) = save_app_settings(&app_settings) {
+                                        status_msg.set(e);
+                                    }
+                                    let next = *refresh_trigger.read() + 1; refresh_trigger.set(next);
                                     show_settings_modal.set(false);
                                 }, 
                                 "Speichern"
@@ -940,4 +2289,188 @@ pub fn app() -> Element {
             }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Actions that ask for confirmation unless skipped in the settings.
+const CONFIRMED_ACTIONS: [&str; 5] = ["reset", "discard", "delete-branch", "force-push-lease", "force-push"];
+
+/// Runs a destructive action once it is confirmed and returns the status message.
+fn run_destructive_action(repo: &str, kind: &str, target: &str, user: &str, token: &str, verify: bool, i18n: &I18nService) -> String {
+    match kind {
+        "reset" => match GitHandler::reset_hard(repo, target) {
+            Ok(_) => "Reset successful".to_string(),
+            Err(e) => format!("Reset Error: {}", i18n.translate(&e)),
+        },
+        "discard" => match GitHandler::discard_changes(repo, target) {
+            Ok(_) => "Changes discarded".to_string(),
+            Err(e) => format!("Discard Error: {}", i18n.translate(&e)),
+        },
+        "delete-branch" => match GitHandler::delete_branch(repo, target) {
+            Ok(_) => "Branch deleted".to_string(),
+            Err(e) => format!("Error: {}", i18n.translate(&e)),
+        },
+        _ => {
+            let mode = if kind == "force-push-lease" { "lease" } else { "force" };
+            match GitHandler::push(repo, user, token, mode, verify) {
+                Ok(_) => "Force Push successful".to_string(),
+                Err(e) => format!("Force Push Error: {}", i18n.translate(&e)),
+            }
+        }
+    }
+}
+
+/// The diff as unified lines or as old and new side by side. Both sides are cells of
+/// one grid, so they scroll together and changed lines stay aligned.
+fn diff_view(lines: &[DiffLine], side_by_side: bool) -> Element {
+    if !side_by_side {
+        return rsx! {
+            for line in lines.iter() {
+                {diff_line_view(line)}
+            }
+        };
+    }
+    rsx! {
+        div { class: "diff-split",
+            for (left, right) in diff::side_by_side(lines) {
+                match (left.map(|i| &lines[i]), right.map(|i| &lines[i])) {
+                    (Some(line), _) if line.kind == LineKind::Header => rsx! {
+                        div { class: "diff-split-header", {segments_view(&line.segments, "")} }
+                    },
+                    (old, new) => rsx! {
+                        {diff_cell(old, true)}
+                        {diff_cell(new, false)}
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Line number and code of one side of a side-by-side row.
+fn diff_cell(line: Option<&DiffLine>, old_side: bool) -> Element {
+    let Some(line) = line else {
+        return rsx! { div { class: "diff-num" } div { class: "diff-cell empty" } };
+    };
+    let number = if old_side { line.old_line } else { line.new_line }.map(|n| n.to_string()).unwrap_or_default();
+    let (cell_class, word_class) = match line.kind {
+        LineKind::Added => ("diff-cell added", "diff-word-add"),
+        LineKind::Removed => ("diff-cell removed", "diff-word-del"),
+        LineKind::Header | LineKind::Marker | LineKind::Context => ("diff-cell", ""),
+    };
+    rsx! {
+        div { class: "diff-num", "{number}" }
+        div { class: "{cell_class}", {segments_view(&line.code(), word_class)} }
+    }
+}
+
+fn segments_view(segments: &[Segment], word_class: &str) -> Element {
+    rsx! {
+        for segment in segments.iter() {
+            {
+                let class = if segment.changed { format!("{} {}", segment.syntax, word_class) } else { segment.syntax.to_string() };
+                rsx! { span { class: "{class}", "{segment.text}" } }
+            }
+        }
+    }
+}
+
+/// One diff line with its old and new line number, syntax colors and the words that
+/// changed against its paired line highlighted.
+fn diff_line_view(line: &DiffLine) -> Element {
+    let (line_bg, word_class) = match line.kind {
+        LineKind::Added => ("rgba(166, 227, 161, 0.2)", "diff-word-add"),
+        LineKind::Removed => ("rgba(243, 139, 168, 0.2)", "diff-word-del"),
+        LineKind::Header | LineKind::Marker | LineKind::Context => ("transparent", ""),
+    };
+    let old_number = line.old_line.map(|n| n.to_string()).unwrap_or_default();
+    let new_number = line.new_line.map(|n| n.to_string()).unwrap_or_default();
+    rsx! {
+        div { style: "background: {line_bg}; line-height: 1.4; white-space: pre;",
+            span { class: "diff-gutter", "{old_number}" }
+            span { class: "diff-gutter", "{new_number}" }
+            {segments_view(&line.segments, word_class)}
+        }
+    }
+}
+
+fn load_action_preview(repo: &str, kind: &str, target: &str) -> Result<ActionPreview, String> {
+    match kind {
+        "reset" => GitHandler::preview_reset_hard(repo, target),
+        "discard" => GitHandler::preview_discard(repo, target),
+        "delete-branch" => GitHandler::preview_delete_branch(repo, target),
+        _ => GitHandler::preview_force_push(repo),
+    }
+}
+
+fn confirm_title_key(kind: &str) -> String {
+    format!("confirm-{}-title", kind)
+}
+
+fn action_label_key(kind: &str) -> &'static str {
+    match kind {
+        "reset" => "menu-reset",
+        "discard" => "menu-discard",
+        "delete-branch" => "menu-del-branch",
+        "force-push-lease" => "btn-force-push-lease",
+        _ => "btn-force-push",
+    }
+}
+
+fn conventional_rules(settings: &RepoSettings) -> Option<ConventionalRules> {
+    settings.conventional_commits.then(|| ConventionalRules { types: settings.conventional_types.clone(), scopes: settings.conventional_scopes.clone() })
+}
+
+/// Parses a comma-separated settings field.
+fn split_list(input: &str) -> Vec<String> {
+    input.split(',').map(|v| v.trim().to_string()).filter(|v| !v.is_empty()).collect()
+}
+
+fn update_repo_settings(repo: &str, update: impl FnOnce(&mut RepoSettings)) -> Result<(), String> {
+    let mut settings = load_repo_settings(repo);
+    update(&mut settings);
+    save_repo_settings(repo, &settings)
+}
+
+fn all_ref_names(repo: &str) -> Vec<String> {
+    let local = GitHandler::get_branches(repo).unwrap_or_default().into_iter().map(|b| format!("refs/heads/{}", b));
+    let remote = GitHandler::get_remote_branches(repo).unwrap_or_default().into_iter().map(|b| format!("refs/remotes/{}", b));
+    let tags = GitHandler::get_tags(repo).unwrap_or_default().into_iter().map(|t| format!("refs/tags/{}", t));
+    local.chain(remote).chain(tags).collect()
+}
+
+fn is_ref_visible(settings: &RepoSettings, full_ref: &str, head_ref: &str) -> bool {
+    if !settings.show_all_refs {
+        return full_ref == head_ref;
+    }
+    if settings.hide_remotes && full_ref.starts_with("refs/remotes/") {
+        return false;
+    }
+    !settings.hidden_refs.iter().any(|r| r == full_ref)
+}
+
+/// Toggles whether `full_ref` seeds the history walk. Coming from the plain HEAD
+/// view, the selection starts out with only the current branch checked.
+fn toggle_ref_visibility(repo: &str, full_ref: &str) -> Result<(), String> {
+    let all_refs = all_ref_names(repo);
+    let head_ref = GitHandler::get_current_branch(repo).map(|b| format!("refs/heads/{}", b)).unwrap_or_default();
+    update_repo_settings(repo, |settings| {
+        if !settings.show_all_refs {
+            settings.show_all_refs = true;
+            settings.hide_remotes = false;
+            settings.hidden_refs = all_refs.iter().filter(|r| **r != head_ref).cloned().collect();
+        }
+        if settings.hide_remotes && full_ref.starts_with("refs/remotes/") {
+            settings.hide_remotes = false;
+            for r in all_refs.iter().filter(|r| r.starts_with("refs/remotes/")) {
+                if !settings.hidden_refs.contains(r) {
+                    settings.hidden_refs.push(r.clone());
+                }
+            }
+        }
+        if let Some(pos) = settings.hidden_refs.iter().position(|r| r == full_ref) {
+            settings.hidden_refs.remove(pos);
+        } else {
+            settings.hidden_refs.push(full_ref.to_string());
+        }
+    })
+}
@@ -0,0 +1,318 @@
+/// How a line of unified diff text is shown.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineKind {
+    Header, // File headers and @@ lines
+    Marker, // "\ No newline at end of file", which has no line number
+    Context,
+    Added,
+    Removed,
+}
+
+/// A piece of a diff line. `changed` marks words that differ from the paired line on
+/// the other side, `syntax` is the CSS class of its syntax highlighting ("" for none).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Segment {
+    pub text: String,
+    pub changed: bool,
+    pub syntax: &'static str,
+}
+
+impl Segment {
+    fn new(text: &str, changed: bool) -> Self {
+        Segment { text: text.to_string(), changed, syntax: "" }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    pub segments: Vec<Segment>,
+    pub old_line: Option<usize>, // Line numbers from the hunk header, None on the side the line is not on
+    pub new_line: Option<usize>,
+}
+
+impl DiffLine {
+    pub fn text(&self) -> String {
+        self.segments.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    /// The segments without the `+`, `-` or space the line starts with.
+    pub fn code(&self) -> Vec<Segment> {
+        let mut segments = self.segments.clone();
+        if !matches!(self.kind, LineKind::Header | LineKind::Marker) {
+            if let Some(first) = segments.first_mut() {
+                first.text.remove(0);
+            }
+            segments.retain(|s| !s.text.is_empty());
+        }
+        segments
+    }
+}
+
+// Pairs with more tokens than this are not compared word by word
+const WORD_DIFF_TOKEN_LIMIT: usize = 400;
+
+/// Parses unified diff text and marks the changed words of removed and added lines.
+/// Like `git diff --word-diff`, the n-th removed line of a block is compared to the
+/// n-th added line that follows it.
+pub fn parse(diff: &str) -> Vec<DiffLine> {
+    let mut in_hunk = false;
+    let (mut old_no, mut new_no) = (0, 0);
+    let mut lines: Vec<DiffLine> = diff.lines().map(|line| {
+        // Inside a hunk "---" is a removed line starting with "--", not a file header
+        if line.starts_with("@@") || line.starts_with("diff ") {
+            in_hunk = line.starts_with("@@");
+        }
+        if let Some((old_start, new_start)) = hunk_starts(line) {
+            (old_no, new_no) = (old_start, new_start);
+        }
+        let kind = if in_hunk { line_kind(line) } else { LineKind::Header };
+        let (old_line, new_line) = match kind {
+            LineKind::Header | LineKind::Marker => (None, None),
+            LineKind::Context => (Some(old_no), Some(new_no)),
+            LineKind::Removed => (Some(old_no), None),
+            LineKind::Added => (None, Some(new_no)),
+        };
+        if old_line.is_some() { old_no += 1; }
+        if new_line.is_some() { new_no += 1; }
+        DiffLine { kind, segments: vec![Segment::new(line, false)], old_line, new_line }
+    }).collect();
+
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].kind != LineKind::Removed {
+            i += 1;
+            continue;
+        }
+        let (removed, added) = change_block(&lines, &mut i);
+        let is_line = |&&n: &&usize| lines[n].kind != LineKind::Marker;
+        let pairs: Vec<(usize, usize)> = removed.iter().filter(is_line).copied().zip(added.iter().filter(is_line).copied()).collect();
+        for (r, a) in pairs {
+            let old = lines[r].text();
+            let new = lines[a].text();
+            if let Some((old_segments, new_segments)) = word_diff(&old[1..], &new[1..]) {
+                lines[r].segments = with_prefix('-', old_segments);
+                lines[a].segments = with_prefix('+', new_segments);
+            }
+        }
+    }
+    lines
+}
+
+/// Aligns old and new lines for a side-by-side view, as indices into `lines`. Context
+/// and header lines are on both sides; in a block of changes the n-th removed line is
+/// next to the n-th added line and the shorter side is padded with `None`.
+pub fn side_by_side(lines: &[DiffLine]) -> Vec<(Option<usize>, Option<usize>)> {
+    let mut rows = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        match lines[i].kind {
+            LineKind::Header | LineKind::Marker | LineKind::Context => {
+                rows.push((Some(i), Some(i)));
+                i += 1;
+            }
+            LineKind::Removed | LineKind::Added => {
+                let (removed, added) = change_block(lines, &mut i);
+                for n in 0..removed.len().max(added.len()) {
+                    rows.push((removed.get(n).copied(), added.get(n).copied()));
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// The removed and then the added lines of the block of changes at `*i`, and moves
+/// `*i` past it. A no-newline marker stays on the side of the line it belongs to.
+fn change_block(lines: &[DiffLine], i: &mut usize) -> (Vec<usize>, Vec<usize>) {
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    while *i < lines.len() && matches!(lines[*i].kind, LineKind::Removed | LineKind::Marker) {
+        removed.push(*i);
+        *i += 1;
+    }
+    while *i < lines.len() && matches!(lines[*i].kind, LineKind::Added | LineKind::Marker) {
+        added.push(*i);
+        *i += 1;
+    }
+    (removed, added)
+}
+
+/// The first old and new line number of a `@@ -a,b +c,d @@` header.
+fn hunk_starts(line: &str) -> Option<(usize, usize)> {
+    let mut parts = line.strip_prefix("@@ ")?.split(' ');
+    let old = parts.next()?.strip_prefix('-')?;
+    let new = parts.next()?.strip_prefix('+')?;
+    let start = |range: &str| range.split(',').next().and_then(|n| n.parse().ok());
+    Some((start(old)?, start(new)?))
+}
+
+fn line_kind(line: &str) -> LineKind {
+    if line.starts_with("@@") {
+        LineKind::Header
+    } else if line.starts_with('+') {
+        LineKind::Added
+    } else if line.starts_with('-') {
+        LineKind::Removed
+    } else if line.starts_with('\\') {
+        LineKind::Marker
+    } else {
+        LineKind::Context
+    }
+}
+
+fn with_prefix(prefix: char, mut segments: Vec<Segment>) -> Vec<Segment> {
+    segments.insert(0, Segment::new(&prefix.to_string(), false));
+    segments
+}
+
+/// Words, runs of whitespace and single punctuation characters.
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let class = char_class(c);
+        if class == 2 {
+            tokens.push(&text[i..i + c.len_utf8()]);
+            start = i + c.len_utf8();
+            continue;
+        }
+        match chars.peek() {
+            Some(&(_, next)) if char_class(next) == class => {}
+            Some(&(j, _)) => {
+                tokens.push(&text[start..j]);
+                start = j;
+            }
+            None => tokens.push(&text[start..]),
+        }
+    }
+    tokens
+}
+
+// 0: word, 1: whitespace, 2: anything else, one token per character
+fn char_class(c: char) -> u8 {
+    if c.is_alphanumeric() || c == '_' {
+        0
+    } else if c.is_whitespace() {
+        1
+    } else {
+        2
+    }
+}
+
+/// Segments of both lines with the tokens outside their longest common subsequence
+/// marked. `None` when the lines have nothing but whitespace in common, or are too long.
+fn word_diff(old: &str, new: &str) -> Option<(Vec<Segment>, Vec<Segment>)> {
+    let a = tokenize(old);
+    let b = tokenize(new);
+    if a.len() > WORD_DIFF_TOKEN_LIMIT || b.len() > WORD_DIFF_TOKEN_LIMIT {
+        return None;
+    }
+
+    // lcs[i][j]: length of the common subsequence of a[i..] and b[j..]
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+    let mut old_changed = vec![true; a.len()];
+    let mut new_changed = vec![true; b.len()];
+    let (mut i, mut j) = (0, 0);
+    let mut common_words = false;
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            old_changed[i] = false;
+            new_changed[j] = false;
+            common_words |= !a[i].trim().is_empty();
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    if !common_words {
+        return None;
+    }
+    Some((merge_segments(&a, &old_changed), merge_segments(&b, &new_changed)))
+}
+
+/// Joins neighbouring tokens with the same state into one segment.
+fn merge_segments(tokens: &[&str], changed: &[bool]) -> Vec<Segment> {
+    let mut segments: Vec<Segment> = Vec::new();
+    for (token, &is_changed) in tokens.iter().zip(changed) {
+        match segments.last_mut() {
+            Some(last) if last.changed == is_changed => last.text.push_str(token),
+            _ => segments.push(Segment::new(token, is_changed)),
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(segments: &[Segment]) -> Vec<(&str, bool)> {
+        segments.iter().map(|s| (s.text.as_str(), s.changed)).collect()
+    }
+
+    #[test]
+    fn word_diff_marks_only_changed_words() {
+        let (old, new) = word_diff("let x = 1;", "let y = 1;").unwrap();
+        assert_eq!(texts(&old), [("let ", false), ("x", true), (" = 1;", false)]);
+        assert_eq!(texts(&new), [("let ", false), ("y", true), (" = 1;", false)]);
+    }
+
+    #[test]
+    fn word_diff_skips_lines_without_common_words() {
+        assert_eq!(word_diff("foo bar", "baz qux"), None);
+    }
+
+    #[test]
+    fn parse_numbers_lines_from_the_hunk_header() {
+        let lines = parse("diff --git a/f b/f\n--- a/f\n+++ b/f\n@@ -3,3 +3,3 @@\n a\n-b\n+c\n d\n");
+        let numbers: Vec<_> = lines.iter().map(|l| (l.kind, l.old_line, l.new_line)).collect();
+        assert_eq!(numbers, [
+            (LineKind::Header, None, None),
+            (LineKind::Header, None, None),
+            (LineKind::Header, None, None),
+            (LineKind::Header, None, None),
+            (LineKind::Context, Some(3), Some(3)),
+            (LineKind::Removed, Some(4), None),
+            (LineKind::Added, None, Some(4)),
+            (LineKind::Context, Some(5), Some(5)),
+        ]);
+    }
+
+    #[test]
+    fn parse_treats_removed_dashes_in_a_hunk_as_removed() {
+        let lines = parse("@@ -1 +0,0 @@\n--- x\n");
+        assert_eq!(lines[1].kind, LineKind::Removed);
+    }
+
+    #[test]
+    fn parse_pairs_lines_across_the_no_newline_marker() {
+        let lines = parse("@@ -1,2 +1,2 @@\n a\n-let x = 1;\n\\ No newline at end of file\n+let y = 1;\n");
+        assert_eq!(lines[3].kind, LineKind::Marker);
+        assert_eq!((lines[3].old_line, lines[3].new_line), (None, None));
+        assert_eq!(lines[4].new_line, Some(2));
+        assert!(lines[2].segments.iter().any(|s| s.changed && s.text == "x"));
+        assert!(lines[4].segments.iter().any(|s| s.changed && s.text == "y"));
+    }
+
+    #[test]
+    fn side_by_side_pads_the_shorter_side() {
+        let lines = parse("@@ -1,3 +1,2 @@\n a\n-b\n-c\n+d\n");
+        assert_eq!(side_by_side(&lines), [(Some(0), Some(0)), (Some(1), Some(1)), (Some(2), Some(4)), (Some(3), None)]);
+    }
+
+    #[test]
+    fn side_by_side_keeps_the_marker_on_its_side() {
+        let lines = parse("@@ -1 +1 @@\n-a\n\\ No newline at end of file\n+b\n");
+        assert_eq!(side_by_side(&lines), [(Some(0), Some(0)), (Some(1), Some(3)), (Some(2), None)]);
+    }
+}
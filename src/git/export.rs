@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::graph::{generate_graph, GRAPH_COLORS, ROW_HEIGHT};
+use super::{GitHandler, HistoryOptions, RefLabel};
+
+const TEXT_GAP: f64 = 20.0;
+const CHAR_WIDTH: f64 = 7.2;
+const DATE_WIDTH: f64 = 140.0;
+const AUTHOR_WIDTH: f64 = 180.0;
+const FONT: &str = "Inter, 'Segoe UI', 'DejaVu Sans', Arial, sans-serif";
+
+/// Renders a range of history (graph, ref labels, summaries, dates and authors)
+/// into a standalone SVG document.
+pub fn export_graph_svg(repo_path: &str, range: &str, limit: usize, all_branches: bool, options: &HistoryOptions) -> Result<String, String> {
+    let commits = GitHandler::get_commits_in_range(repo_path, range, limit, all_branches, options)?;
+    if commits.is_empty() {
+        return Err("err-export-empty".to_string());
+    }
+    let refs = GitHandler::get_commit_refs(repo_path).unwrap_or_default();
+    Ok(render_svg(&commits, &refs))
+}
+
+/// Writes the graph to `out`, rasterizing it when the file name ends in `.png`.
+pub fn export_graph_to_file(repo_path: &str, range: &str, limit: usize, all_branches: bool, options: &HistoryOptions, out: &Path) -> Result<(), String> {
+    let svg = export_graph_svg(repo_path, range, limit, all_branches, options)?;
+    let is_png = out.extension().and_then(|e| e.to_str()).map(|e| e.eq_ignore_ascii_case("png")).unwrap_or(false);
+    if is_png {
+        rasterize_png(&svg, out)
+    } else {
+        std::fs::write(out, svg).map_err(|e| e.to_string())
+    }
+}
+
+pub fn render_svg(commits: &[(String, String, String, String, Vec<String>)], refs: &HashMap<String, Vec<RefLabel>>) -> String {
+    let nodes = generate_graph(commits, &[]);
+
+    let graph_width = nodes.iter().map(|n| n.cx).fold(0.0, f64::max) + ROW_HEIGHT / 2.0;
+    let text_x = graph_width + TEXT_GAP;
+    let summary_width = commits.iter()
+        .map(|(sha, summary, _, _, _)| {
+            let labels: usize = refs.get(sha).map(|l| l.iter().map(|r| r.name.chars().count() + 3).sum()).unwrap_or(0);
+            (summary.chars().count() + labels) as f64 * CHAR_WIDTH
+        })
+        .fold(0.0, f64::max);
+    let date_x = text_x + summary_width + TEXT_GAP;
+    let author_x = date_x + DATE_WIDTH;
+    let width = (author_x + AUTHOR_WIDTH).ceil();
+    let height = (nodes.len() as f64 * ROW_HEIGHT).ceil();
+
+    let mut svg = String::new();
+    svg.push_str(&format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+        w = width, h = height
+    ));
+    svg.push_str(&format!("<rect width=\"100%\" height=\"100%\" fill=\"#1e1e2e\"/>\n<g font-family=\"{}\" font-size=\"13\">\n", FONT));
+
+    for node in nodes.iter() {
+        for (idx, path_d) in node.paths.iter().enumerate() {
+            let color = GRAPH_COLORS[*node.path_colors.get(idx).unwrap_or(&node.color_index)];
+            svg.push_str(&format!(
+                "<path d=\"{}\" stroke=\"{}\" stroke-width=\"4\" fill=\"none\" stroke-linecap=\"round\" stroke-linejoin=\"round\"/>\n",
+                path_d, color
+            ));
+        }
+    }
+
+    let commit_info: HashMap<&str, &(String, String, String, String, Vec<String>)> = commits.iter().map(|c| (c.0.as_str(), c)).collect();
+    for node in nodes.iter() {
+        let color = GRAPH_COLORS[node.color_index];
+        svg.push_str(&format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"#1e1e2e\" stroke=\"{}\" stroke-width=\"3\"/>\n",
+            node.cx, node.cy, node.r, color
+        ));
+
+        let Some((sha, summary, author, time, _)) = commit_info.get(node.sha.as_str()).copied() else { continue };
+        let baseline = node.cy + 4.5;
+        let mut x = text_x;
+        for label in refs.get(sha).map(|l| l.as_slice()).unwrap_or(&[]) {
+            let label_width = (label.name.chars().count() as f64 + 1.5) * CHAR_WIDTH;
+            let fill = if label.kind == "tag" { "none".to_string() } else { format!("{}33", color) };
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"18\" rx=\"9\" fill=\"{}\" stroke=\"{}\"/>\n",
+                x, node.cy - 9.0, label_width, fill, color
+            ));
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" font-size=\"11\" font-weight=\"{}\" fill=\"#cdd6f4\">{}</text>\n",
+                x + 0.75 * CHAR_WIDTH, baseline - 1.0, if label.is_head { "800" } else { "600" }, escape_xml(&label.name)
+            ));
+            x += label_width + 1.5 * CHAR_WIDTH;
+        }
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" fill=\"#cdd6f4\">{}</text>\n", x, baseline, escape_xml(summary)));
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" fill=\"#a6adc8\">{}</text>\n", date_x, baseline, escape_xml(time)));
+        svg.push_str(&format!("<text x=\"{}\" y=\"{}\" fill=\"#a6adc8\">{}</text>\n", author_x, baseline, escape_xml(author)));
+    }
+
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
+
+pub fn rasterize_png(svg: &str, out: &Path) -> Result<(), String> {
+    let mut options = resvg::usvg::Options::default();
+    options.fontdb_mut().load_system_fonts();
+    let tree = resvg::usvg::Tree::from_str(svg, &options).map_err(|e| e.to_string())?;
+    let size = tree.size().to_int_size();
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(size.width(), size.height()).ok_or("err-export-too-large".to_string())?;
+    resvg::render(&tree, resvg::tiny_skia::Transform::default(), &mut pixmap.as_mut());
+    pixmap.save_png(out).map_err(|e| e.to_string())
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(sha: &str, summary: &str, parents: &[&str]) -> (String, String, String, String, Vec<String>) {
+        (sha.to_string(), summary.to_string(), "Test".to_string(), "2024-01-01 12:00".to_string(), parents.iter().map(|p| p.to_string()).collect())
+    }
+
+    #[test]
+    fn svg_has_a_circle_per_commit_with_escaped_text_and_labels() {
+        let commits = vec![
+            commit("m", "Merge <feature> & more", &["c", "b"]),
+            commit("c", "main work", &["a"]),
+            commit("b", "feature work", &["a"]),
+            commit("a", "initial", &[]),
+        ];
+        let mut refs = HashMap::new();
+        refs.insert("m".to_string(), vec![RefLabel { name: "v1.0".to_string(), kind: "tag".to_string(), is_head: false }]);
+
+        let svg = render_svg(&commits, &refs);
+        assert_eq!(svg.matches("<circle ").count(), commits.len());
+        assert!(svg.contains(">Merge &lt;feature&gt; &amp; more</text>"));
+        assert!(!svg.contains("<feature>"));
+        assert!(svg.contains(">v1.0</text>"));
+        assert!(resvg::usvg::Tree::from_str(&svg, &resvg::usvg::Options::default()).is_ok());
+    }
+}
@@ -1,16 +1,33 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Clone, Debug)]
 pub struct GraphNode {
     pub sha: String,
+    pub kind: String,            // "commit", "wip" or "stash"
+    pub row: usize,              // Row index in the rendered history
     pub paths: Vec<String>,      // SVG path 'd' attributes
     pub path_colors: Vec<usize>, // Color index for each path
+    pub path_parents: Vec<String>,       // Parent sha each path leads to
+    pub through_paths: Vec<String>,      // Edges from rows above that pass through this row
+    pub through_colors: Vec<usize>,      // Color index for each through path
+    pub through_edges: Vec<(String, String)>, // (child sha, parent sha) of each through path
     pub cx: f64,                 // Circle center x
     pub cy: f64,                 // Circle center y
     pub r: f64,                  // Circle radius
     pub color_index: usize,      // Main color for this node
 }
 
+/// A row that is not a commit of the revwalk but is drawn in the graph,
+/// e.g. the uncommitted working copy or a stash entry.
+#[derive(Clone, Debug)]
+pub struct VirtualRow {
+    pub sha: String,
+    pub parent: Option<String>,
+    pub kind: String, // "wip" or "stash"
+}
+
+pub const WIP_SHA: &str = "WIP";
+
 pub const GRAPH_COLORS: &[&str] = &[
     "#4A90E2", // Blue
     "#F5A623", // Orange
@@ -23,33 +40,40 @@ pub const GRAPH_COLORS: &[&str] = &[
 ];
 
 const LANE_WIDTH: f64 = 30.0;
-const ROW_HEIGHT: f64 = 40.0;
+pub const ROW_HEIGHT: f64 = 40.0;
 const CIRCLE_RADIUS: f64 = 5.5;
 const LINE_OFFSET: f64 = LANE_WIDTH / 2.0;
 
-pub fn generate_graph(commits: &[(String, String, String, String, Vec<String>)]) -> Vec<GraphNode> {
-    if commits.is_empty() {
+struct Row {
+    sha: String,
+    parents: Vec<String>,
+    kind: String,
+}
+
+pub fn generate_graph(commits: &[(String, String, String, String, Vec<String>)], virtual_rows: &[VirtualRow]) -> Vec<GraphNode> {
+    let rows = build_rows(commits, virtual_rows);
+    if rows.is_empty() {
         return vec![];
     }
 
     let mut sha_to_row: HashMap<String, usize> = HashMap::new();
-    for (i, (sha, _, _, _, _)) in commits.iter().enumerate() {
-        sha_to_row.insert(sha.clone(), i);
+    for (i, row) in rows.iter().enumerate() {
+        sha_to_row.insert(row.sha.clone(), i);
     }
 
-    let (commit_lanes, lane_colors) = assign_lanes_and_colors(commits);
+    let (commit_lanes, lane_colors) = assign_lanes_and_colors(&rows);
 
     let mut nodes = Vec::new();
 
-    for (current_row, (sha, _, _, _, parents)) in commits.iter().enumerate() {
-        let current_lane = *commit_lanes.get(sha).unwrap_or(&0);
+    for (current_row, row) in rows.iter().enumerate() {
+        let current_lane = *commit_lanes.get(&row.sha).unwrap_or(&0);
         let current_color_idx = *lane_colors.get(&current_lane).unwrap_or(&0);
 
+        let path_parents: Vec<String> = row.parents.iter().filter(|p| sha_to_row.contains_key(*p)).cloned().collect();
         let (paths, path_colors) = generate_paths(
             current_row,
             current_lane,
-            current_color_idx,
-            parents,
+            &row.parents,
             &sha_to_row,
             &commit_lanes,
             &lane_colors,
@@ -59,9 +83,15 @@ pub fn generate_graph(commits: &[(String, String, String, String, Vec<String>)])
         let cy = (current_row as f64) * ROW_HEIGHT + ROW_HEIGHT / 2.0;
 
         nodes.push(GraphNode {
-            sha: sha.clone(),
+            sha: row.sha.clone(),
+            kind: row.kind.clone(),
+            row: current_row,
             paths,
             path_colors,
+            path_parents,
+            through_paths: Vec::new(),
+            through_colors: Vec::new(),
+            through_edges: Vec::new(),
             cx,
             cy,
             r: CIRCLE_RADIUS,
@@ -69,68 +99,121 @@ pub fn generate_graph(commits: &[(String, String, String, String, Vec<String>)])
         });
     }
 
+    // Each row is rendered in its own viewport, so edges spanning several rows
+    // are repeated on every row they cross.
+    for current_row in 0..nodes.len() {
+        let parent_rows: Vec<usize> = rows[current_row].parents.iter().filter_map(|p| sha_to_row.get(p).copied()).collect();
+        for (idx, parent_row) in parent_rows.into_iter().enumerate() {
+            let path_d = nodes[current_row].paths[idx].clone();
+            let color = nodes[current_row].path_colors[idx];
+            let edge = (rows[current_row].sha.clone(), rows[parent_row].sha.clone());
+            for through_row in (current_row + 1)..=parent_row {
+                nodes[through_row].through_paths.push(path_d.clone());
+                nodes[through_row].through_colors.push(color);
+                nodes[through_row].through_edges.push(edge.clone());
+            }
+        }
+    }
+
     nodes
 }
 
-fn assign_lanes_and_colors(
-    commits: &[(String, String, String, String, Vec<String>)],
-) -> (HashMap<String, usize>, HashMap<usize, usize>) {
+/// Returns the selected commit together with all of its ancestors and
+/// descendants among the rows of the graph.
+pub fn ancestry_path(commits: &[(String, String, String, String, Vec<String>)], virtual_rows: &[VirtualRow], selected: &str) -> HashSet<String> {
+    let rows = build_rows(commits, virtual_rows);
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    let mut parents: HashMap<&str, &[String]> = HashMap::new();
+    for row in rows.iter() {
+        parents.insert(row.sha.as_str(), row.parents.as_slice());
+        for parent in row.parents.iter() {
+            children.entry(parent.as_str()).or_default().push(row.sha.as_str());
+        }
+    }
+
+    let mut related: HashSet<String> = HashSet::new();
+    related.insert(selected.to_string());
+
+    let mut stack: Vec<&str> = vec![selected];
+    while let Some(sha) = stack.pop() {
+        for parent in parents.get(sha).copied().unwrap_or(&[]) {
+            if related.insert(parent.clone()) {
+                stack.push(parent.as_str());
+            }
+        }
+    }
+
+    let mut descendants: HashSet<&str> = HashSet::new();
+    let mut stack: Vec<&str> = vec![selected];
+    while let Some(sha) = stack.pop() {
+        for child in children.get(sha).cloned().unwrap_or_default() {
+            if descendants.insert(child) {
+                related.insert(child.to_string());
+                stack.push(child);
+            }
+        }
+    }
+
+    related
+}
+
+/// Merges the virtual rows into the commit list: the working copy goes on top,
+/// stash entries directly above the commit they were created from.
+fn build_rows(commits: &[(String, String, String, String, Vec<String>)], virtual_rows: &[VirtualRow]) -> Vec<Row> {
+    let mut rows = Vec::new();
+
+    for v in virtual_rows.iter().filter(|v| v.kind == "wip") {
+        rows.push(Row { sha: v.sha.clone(), parents: v.parent.iter().cloned().collect(), kind: v.kind.clone() });
+    }
+
+    for (sha, _, _, _, parents) in commits.iter() {
+        for v in virtual_rows.iter().filter(|v| v.kind != "wip" && v.parent.as_ref() == Some(sha)) {
+            rows.push(Row { sha: v.sha.clone(), parents: vec![sha.clone()], kind: v.kind.clone() });
+        }
+        rows.push(Row { sha: sha.clone(), parents: parents.clone(), kind: "commit".to_string() });
+    }
+
+    rows
+}
+
+fn assign_lanes_and_colors(rows: &[Row]) -> (HashMap<String, usize>, HashMap<usize, usize>) {
     let mut commit_lanes: HashMap<String, usize> = HashMap::new();
     let mut lane_colors: HashMap<usize, usize> = HashMap::new();
+    // Each lane holds the sha it is waiting for next (a parent of a row above)
     let mut active_lanes: Vec<Option<String>> = Vec::new();
     let mut next_color: usize = 0;
 
-    for (sha, _, _, _, parents) in commits.iter() {
-        let assigned_lane = if let Some(parent_sha) = parents.first() {
-            if let Some(&parent_lane) = commit_lanes.get(parent_sha) {
-                if active_lanes
-                    .get(parent_lane)
-                    .and_then(|l| l.as_ref())
-                    .map(|l| l == parent_sha)
-                    .unwrap_or(false)
-                {
-                    Some(parent_lane)
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        };
+    for row in rows.iter() {
+        let sha = &row.sha;
+        let waiting_lane = active_lanes.iter().position(|l| l.as_ref() == Some(sha));
 
-        let lane = assigned_lane.unwrap_or_else(|| {
-            if let Some(free) = active_lanes.iter().position(|l| l.is_none()) {
-                free
-            } else {
-                active_lanes.push(None);
-                active_lanes.len() - 1
-            }
-        });
+        let lane = waiting_lane.unwrap_or_else(|| free_lane(&mut active_lanes));
 
-        while active_lanes.len() <= lane {
-            active_lanes.push(None);
+        // Other lanes converging into this commit end here
+        for slot in active_lanes.iter_mut() {
+            if slot.as_ref() == Some(sha) {
+                *slot = None;
+            }
         }
 
         commit_lanes.insert(sha.clone(), lane);
-        active_lanes[lane] = Some(sha.clone());
 
         if !lane_colors.contains_key(&lane) {
             lane_colors.insert(lane, next_color % GRAPH_COLORS.len());
             next_color += 1;
         }
 
-        for parent_sha in parents.iter().skip(1) {
-            if let Some(&parent_lane) = commit_lanes.get(parent_sha) {
-                if active_lanes
-                    .get(parent_lane)
-                    .and_then(|l| l.as_ref())
-                    .map(|l| l == parent_sha)
-                    .unwrap_or(false)
-                {
-                    active_lanes[parent_lane] = None;
-                }
+        // The first parent continues this lane unless another lane already waits for it
+        if let Some(parent_sha) = row.parents.first() {
+            if !active_lanes.iter().any(|l| l.as_ref() == Some(parent_sha)) && !commit_lanes.contains_key(parent_sha) {
+                active_lanes[lane] = Some(parent_sha.clone());
+            }
+        }
+
+        for parent_sha in row.parents.iter().skip(1) {
+            if !active_lanes.iter().any(|l| l.as_ref() == Some(parent_sha)) && !commit_lanes.contains_key(parent_sha) {
+                let parent_lane = free_lane(&mut active_lanes);
+                active_lanes[parent_lane] = Some(parent_sha.clone());
             }
         }
     }
@@ -138,10 +221,18 @@ fn assign_lanes_and_colors(
     (commit_lanes, lane_colors)
 }
 
+fn free_lane(active_lanes: &mut Vec<Option<String>>) -> usize {
+    if let Some(free) = active_lanes.iter().position(|l| l.is_none()) {
+        free
+    } else {
+        active_lanes.push(None);
+        active_lanes.len() - 1
+    }
+}
+
 fn generate_paths(
     current_row: usize,
     current_lane: usize,
-    current_color: usize,
     parents: &[String],
     sha_to_row: &HashMap<String, usize>,
     commit_lanes: &HashMap<String, usize>,
@@ -165,11 +256,13 @@ fn generate_paths(
                 // Same lane: straight vertical line
                 format!("M {} {} L {} {}", x1, y1, x2, y2)
             } else {
-                // Different lane: smooth cubic Bezier curve
-                let mid_y = (y1 + y2) / 2.0;
+                // Different lane: bend into the parent's lane within the next row,
+                // which stays reserved for the parent until it is reached
+                let bend_y = y1 + ROW_HEIGHT;
+                let mid_y = y1 + ROW_HEIGHT / 2.0;
                 format!(
-                    "M {} {} C {} {} {} {} {} {}",
-                    x1, y1, x1, mid_y, x2, mid_y, x2, y2
+                    "M {} {} C {} {} {} {} {} {} L {} {}",
+                    x1, y1, x1, mid_y, x2, mid_y, x2, bend_y, x2, y2
                 )
             };
 
@@ -179,4 +272,84 @@ fn generate_paths(
     }
 
     (paths, path_colors)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(sha: &str, parents: &[&str]) -> (String, String, String, String, Vec<String>) {
+        (sha.to_string(), String::new(), String::new(), String::new(), parents.iter().map(|p| p.to_string()).collect())
+    }
+
+    fn node<'a>(nodes: &'a [GraphNode], sha: &str) -> &'a GraphNode {
+        nodes.iter().find(|n| n.sha == sha).unwrap()
+    }
+
+    fn edge(child: &str, parent: &str) -> (String, String) {
+        (child.to_string(), parent.to_string())
+    }
+
+    #[test]
+    fn fork_and_merge_get_their_own_lanes_and_through_edges() {
+        let commits = vec![commit("m", &["c", "b"]), commit("c", &["a"]), commit("b", &["a"]), commit("a", &[])];
+        let nodes = generate_graph(&commits, &[]);
+
+        assert_eq!(nodes.iter().map(|n| n.sha.as_str()).collect::<Vec<_>>(), vec!["m", "c", "b", "a"]);
+        assert_eq!(node(&nodes, "m").cx, node(&nodes, "c").cx);
+        assert_eq!(node(&nodes, "c").cx, node(&nodes, "a").cx);
+        assert!(node(&nodes, "b").cx > node(&nodes, "a").cx);
+
+        assert_eq!(node(&nodes, "m").path_parents, vec!["c", "b"]);
+        assert_eq!(node(&nodes, "m").paths.len(), 2);
+        assert_eq!(node(&nodes, "b").path_parents, vec!["a"]);
+
+        assert!(node(&nodes, "m").through_edges.is_empty());
+        assert_eq!(node(&nodes, "c").through_edges, vec![edge("m", "c"), edge("m", "b")]);
+        assert_eq!(node(&nodes, "b").through_edges, vec![edge("m", "b"), edge("c", "a")]);
+        assert_eq!(node(&nodes, "a").through_edges, vec![edge("c", "a"), edge("b", "a")]);
+        for n in nodes.iter() {
+            assert_eq!(n.through_paths.len(), n.through_edges.len());
+            assert_eq!(n.through_colors.len(), n.through_edges.len());
+        }
+    }
+
+    #[test]
+    fn wip_row_sits_on_head_and_stash_above_its_base() {
+        let commits = vec![commit("head", &["base"]), commit("base", &[])];
+        let virtual_rows = vec![
+            VirtualRow { sha: WIP_SHA.to_string(), parent: Some("head".to_string()), kind: "wip".to_string() },
+            VirtualRow { sha: "stash".to_string(), parent: Some("base".to_string()), kind: "stash".to_string() },
+        ];
+        let nodes = generate_graph(&commits, &virtual_rows);
+
+        let order: Vec<(&str, &str)> = nodes.iter().map(|n| (n.sha.as_str(), n.kind.as_str())).collect();
+        assert_eq!(order, vec![(WIP_SHA, "wip"), ("head", "commit"), ("stash", "stash"), ("base", "commit")]);
+
+        let wip = node(&nodes, WIP_SHA);
+        assert_eq!(wip.path_parents, vec!["head"]);
+        assert_eq!(wip.cx, node(&nodes, "head").cx);
+        assert_eq!(node(&nodes, "stash").path_parents, vec!["base"]);
+        assert!(node(&nodes, "stash").cx > node(&nodes, "base").cx);
+        assert_eq!(node(&nodes, "base").through_edges, vec![edge("head", "base"), edge("stash", "base")]);
+    }
+
+    #[test]
+    fn ancestry_path_is_ancestors_and_descendants_only() {
+        let commits = vec![
+            commit("side", &["root"]),
+            commit("m", &["c", "b"]),
+            commit("c", &["a"]),
+            commit("b", &["a"]),
+            commit("a", &["root"]),
+            commit("root", &[]),
+        ];
+
+        let related = ancestry_path(&commits, &[], "c");
+        let expected: HashSet<String> = ["m", "c", "a", "root"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(related, expected);
+
+        let related = ancestry_path(&commits, &[], "root");
+        assert_eq!(related.len(), commits.len());
+    }
+}
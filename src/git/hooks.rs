@@ -0,0 +1,126 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+use chrono::Local;
+use git2::Repository;
+
+/// One execution of a client-side hook, kept for the hook log panel.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HookRun {
+    pub hook: String,
+    pub time: String,
+    pub success: bool,
+    pub output: String, // stdout and stderr of the hook, in that order
+}
+
+const HOOK_LOG_LIMIT: usize = 100;
+
+// Runs of all repositories in this session, newest last
+static HOOK_LOG: OnceLock<Mutex<Vec<HookRun>>> = OnceLock::new();
+
+pub fn log() -> Vec<HookRun> {
+    HOOK_LOG.get_or_init(|| Mutex::new(Vec::new())).lock().map(|l| l.clone()).unwrap_or_default()
+}
+
+pub fn clear_log() {
+    if let Ok(mut l) = HOOK_LOG.get_or_init(|| Mutex::new(Vec::new())).lock() {
+        l.clear();
+    }
+}
+
+/// `core.hooksPath` (relative paths start at the working tree, as in git) or the
+/// `hooks` folder of the shared .git directory.
+pub fn hooks_dir(repo: &Repository) -> PathBuf {
+    let configured = repo.config().ok().and_then(|c| c.get_path("core.hooksPath").ok());
+    match configured {
+        Some(dir) if dir.is_absolute() => dir,
+        Some(dir) => repo.workdir().unwrap_or_else(|| repo.path()).join(dir),
+        None => super::common_dir(repo).join("hooks"),
+    }
+}
+
+/// Runs a hook if it exists. A hook that exits non-zero makes this fail with
+/// "err-hook-failed"; its output is in the hook log either way.
+pub fn run(repo: &Repository, name: &str, args: &[&str], input: &str) -> Result<(), String> {
+    let hook = hooks_dir(repo).join(name);
+    if !is_executable(&hook) {
+        return Ok(());
+    }
+
+    let workdir = repo.workdir().unwrap_or_else(|| repo.path()).to_path_buf();
+    let mut command = hook_command(&hook);
+    command.args(args)
+        .current_dir(&workdir)
+        .env("GIT_DIR", repo.path())
+        .env("GIT_INDEX_FILE", repo.path().join("index"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn().map_err(|e| format!("{}: {}", name, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that does not read its input closes the pipe early, which is fine
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+
+    let run = HookRun {
+        hook: name.to_string(),
+        time: Local::now().format("%H:%M:%S").to_string(),
+        success: output.status.success(),
+        output: format!("{}{}", String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr)).trim_end().to_string(),
+    };
+    let success = run.success;
+    if let Ok(mut l) = HOOK_LOG.get_or_init(|| Mutex::new(Vec::new())).lock() {
+        l.push(run);
+        let overflow = l.len().saturating_sub(HOOK_LOG_LIMIT);
+        l.drain(..overflow);
+    }
+
+    if success { Ok(()) } else { Err("err-hook-failed".to_string()) }
+}
+
+/// Runs the `prepare-commit-msg` and `commit-msg` hooks on a message the way git
+/// does, through `.git/COMMIT_EDITMSG`, and returns the message they leave behind.
+pub fn run_message_hooks(repo: &Repository, message: &str, verify: bool) -> Result<String, String> {
+    let prepare = hooks_dir(repo).join("prepare-commit-msg");
+    let commit_msg = hooks_dir(repo).join("commit-msg");
+    let needs_commit_msg = verify && is_executable(&commit_msg);
+    if !is_executable(&prepare) && !needs_commit_msg {
+        return Ok(message.to_string());
+    }
+
+    let msg_file = repo.path().join("COMMIT_EDITMSG");
+    std::fs::write(&msg_file, message).map_err(|e| e.to_string())?;
+    let msg_path = msg_file.to_string_lossy().to_string();
+    run(repo, "prepare-commit-msg", &[&msg_path, "message"], "")?;
+    if verify {
+        run(repo, "commit-msg", &[&msg_path], "")?;
+    }
+    std::fs::read_to_string(&msg_file).map_err(|e| e.to_string())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(unix)]
+fn hook_command(hook: &Path) -> Command {
+    Command::new(hook)
+}
+
+// Hooks are shell scripts; on Windows they run through the sh.exe of Git for Windows
+#[cfg(not(unix))]
+fn hook_command(hook: &Path) -> Command {
+    let mut command = Command::new("sh");
+    command.arg(hook);
+    command
+}
@@ -0,0 +1,278 @@
+use git2::Repository;
+
+pub const SUBJECT_LIMIT: usize = 50;
+pub const BODY_LIMIT: usize = 72;
+const RECENT_MESSAGE_LIMIT: usize = 20;
+// Trailer tokens without a hyphen; a paragraph of only `Word: text` lines needs one of these
+const SINGLE_WORD_TRAILERS: &[&str] = &["Fixes", "Closes", "Resolves", "Refs", "Cc", "Bug"];
+
+pub const DEFAULT_CONVENTIONAL_TYPES: &[&str] = &["feat", "fix", "docs", "style", "refactor", "perf", "test", "build", "ci", "chore", "revert"];
+
+/// A problem found in a commit message. `key` is an i18n key, `line` is 1-based.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LintIssue {
+    pub key: String,
+    pub line: usize,
+    pub is_error: bool, // Errors can block the commit, warnings never do
+}
+
+impl LintIssue {
+    fn new(key: &str, line: usize, is_error: bool) -> Self {
+        LintIssue { key: key.to_string(), line, is_error }
+    }
+}
+
+/// Conventional Commits rules of a repository. An empty scope list allows any scope.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConventionalRules {
+    pub types: Vec<String>,
+    pub scopes: Vec<String>,
+}
+
+/// Splits a message into its subject line and the body after the blank line.
+pub fn split(message: &str) -> (String, String) {
+    match message.split_once('\n') {
+        Some((subject, rest)) => (subject.to_string(), rest.strip_prefix('\n').unwrap_or(rest).to_string()),
+        None => (message.to_string(), String::new()),
+    }
+}
+
+pub fn join(subject: &str, body: &str) -> String {
+    if body.is_empty() { subject.to_string() } else { format!("{}\n\n{}", subject, body) }
+}
+
+/// Removes `#` comment lines and surrounding blank lines, like git's default cleanup
+/// of messages written in an editor.
+pub fn cleanup(message: &str) -> String {
+    let lines: Vec<&str> = message.lines().filter(|l| !l.starts_with('#')).map(|l| l.trim_end()).collect();
+    let mut cleaned = String::new();
+    let mut blank_run = false;
+    for line in lines.iter().skip_while(|l| l.is_empty()) {
+        if line.is_empty() {
+            blank_run = true;
+            continue;
+        }
+        if blank_run && !cleaned.is_empty() {
+            cleaned.push('\n');
+        }
+        blank_run = false;
+        cleaned.push_str(line);
+        cleaned.push('\n');
+    }
+    cleaned.trim_end().to_string()
+}
+
+pub fn lint(message: &str, conventional: Option<&ConventionalRules>) -> Vec<LintIssue> {
+    let message = cleanup(message);
+    let mut issues = Vec::new();
+    let mut lines = message.lines();
+    let subject = lines.next().unwrap_or_default();
+
+    if subject.trim().is_empty() {
+        issues.push(LintIssue::new("lint-subject-empty", 1, true));
+        return issues;
+    }
+    if subject.trim_end().ends_with('.') {
+        issues.push(LintIssue::new("lint-subject-period", 1, false));
+    }
+    if subject.chars().count() > SUBJECT_LIMIT {
+        issues.push(LintIssue::new("lint-subject-long", 1, false));
+    }
+    if let Some(second) = lines.next() {
+        if !second.is_empty() {
+            issues.push(LintIssue::new("lint-blank-line", 2, true));
+        }
+    }
+    for (i, line) in message.lines().enumerate().skip(1) {
+        // A single long word (a URL, a path) cannot be wrapped
+        if line.chars().count() > BODY_LIMIT && line.trim().contains(' ') {
+            issues.push(LintIssue::new("lint-body-long", i + 1, false));
+        }
+    }
+    if let Some(rules) = conventional {
+        issues.extend(lint_conventional(subject, rules));
+    }
+    issues
+}
+
+/// Checks `type(scope)!: description` against the allowed types and scopes.
+fn lint_conventional(subject: &str, rules: &ConventionalRules) -> Vec<LintIssue> {
+    let Some((prefix, description)) = subject.split_once(": ") else {
+        return vec![LintIssue::new("lint-cc-format", 1, true)];
+    };
+    let prefix = prefix.strip_suffix('!').unwrap_or(prefix);
+    let (kind, scope) = match prefix.split_once('(') {
+        Some((kind, rest)) => match rest.strip_suffix(')') {
+            Some(scope) => (kind, Some(scope)),
+            None => return vec![LintIssue::new("lint-cc-format", 1, true)],
+        },
+        None => (prefix, None),
+    };
+
+    let mut issues = Vec::new();
+    if description.trim().is_empty() {
+        issues.push(LintIssue::new("lint-cc-format", 1, true));
+    }
+    if !rules.types.iter().any(|t| t == kind) {
+        issues.push(LintIssue::new("lint-cc-type", 1, true));
+    }
+    match scope {
+        Some(s) if s.is_empty() => issues.push(LintIssue::new("lint-cc-format", 1, true)),
+        Some(s) if !rules.scopes.is_empty() && !rules.scopes.iter().any(|allowed| allowed == s) => {
+            issues.push(LintIssue::new("lint-cc-scope", 1, true));
+        }
+        _ => {}
+    }
+    issues
+}
+
+/// Separates the trailer block (the last paragraph, if every line is `Token: value`)
+/// from the rest of the message. A paragraph like `Note: ...` is body text, so the
+/// block needs at least one hyphenated token such as `Signed-off-by` or a known one-word trailer.
+pub fn parse_trailers(message: &str) -> (String, Vec<(String, String)>) {
+    let message = message.trim_end();
+    let (head, last) = match message.rfind("\n\n") {
+        Some(i) => (&message[..i], &message[i + 2..]),
+        None => return (message.to_string(), Vec::new()),
+    };
+    let trailers: Option<Vec<(String, String)>> = last.lines().map(parse_trailer_line).collect();
+    match trailers {
+        Some(t) if t.iter().any(|(token, _)| is_trailer_token(token)) => (head.trim_end().to_string(), t),
+        _ => (message.to_string(), Vec::new()),
+    }
+}
+
+fn parse_trailer_line(line: &str) -> Option<(String, String)> {
+    let (token, value) = line.split_once(": ")?;
+    let valid = !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    valid.then(|| (token.to_string(), value.trim().to_string()))
+}
+
+fn is_trailer_token(token: &str) -> bool {
+    token.contains('-') || SINGLE_WORD_TRAILERS.iter().any(|t| t.eq_ignore_ascii_case(token))
+}
+
+/// Appends `Token: value` to the trailer block, starting one if the message has none.
+/// A trailer that is already present is not added twice.
+pub fn add_trailer(message: &str, token: &str, value: &str) -> String {
+    let (body, mut trailers) = parse_trailers(message);
+    if trailers.iter().any(|(t, v)| t.eq_ignore_ascii_case(token) && v == value) {
+        return message.trim_end().to_string();
+    }
+    trailers.push((token.to_string(), value.to_string()));
+    let block = trailers.iter().map(|(t, v)| format!("{}: {}", t, v)).collect::<Vec<_>>().join("\n");
+    format!("{}\n\n{}", body, block)
+}
+
+/// The message template from `commit.template`, or `.gitmessage` in the working tree.
+pub fn template(repo: &Repository) -> Option<String> {
+    let workdir = repo.workdir()?.to_path_buf();
+    let configured = repo.config().ok().and_then(|c| c.get_path("commit.template").ok());
+    let path = match configured {
+        Some(p) if p.is_absolute() => p,
+        Some(p) => workdir.join(p),
+        None => workdir.join(".gitmessage"),
+    };
+    std::fs::read_to_string(path).ok().filter(|t| !t.trim().is_empty())
+}
+
+/// Full messages of the latest commits on HEAD, without duplicates.
+pub fn recent_messages(repo: &Repository) -> Vec<String> {
+    let Ok(mut revwalk) = repo.revwalk() else { return Vec::new() };
+    if revwalk.push_head().is_err() {
+        return Vec::new();
+    }
+    let mut messages: Vec<String> = Vec::new();
+    for oid in revwalk.flatten() {
+        let Some(message) = repo.find_commit(oid).ok().and_then(|c| c.message().map(|m| m.trim_end().to_string())) else { continue };
+        if !messages.contains(&message) {
+            messages.push(message);
+        }
+        if messages.len() >= RECENT_MESSAGE_LIMIT {
+            break;
+        }
+    }
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(issues: &[LintIssue]) -> Vec<(&str, usize)> {
+        issues.iter().map(|i| (i.key.as_str(), i.line)).collect()
+    }
+
+    fn rules(scopes: &[&str]) -> ConventionalRules {
+        ConventionalRules {
+            types: DEFAULT_CONVENTIONAL_TYPES.iter().map(|t| t.to_string()).collect(),
+            scopes: scopes.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn cleanup_drops_comments_and_extra_blank_lines() {
+        let message = "\n\nSubject  \n# Please enter the commit message\n\n\n\nBody line\n\n# trailing comment\n";
+        assert_eq!(cleanup(message), "Subject\n\nBody line");
+    }
+
+    #[test]
+    fn lint_accepts_a_well_formed_message() {
+        assert!(lint("Add the thing\n\nExplain why the thing is needed.", None).is_empty());
+    }
+
+    #[test]
+    fn lint_reports_subject_problems() {
+        assert_eq!(keys(&lint("# only a comment\n", None)), [("lint-subject-empty", 1)]);
+        assert_eq!(keys(&lint("Add the thing.", None)), [("lint-subject-period", 1)]);
+        let long = "A subject line that goes on for far longer than fifty characters";
+        assert_eq!(keys(&lint(long, None)), [("lint-subject-long", 1)]);
+    }
+
+    #[test]
+    fn lint_reports_body_problems() {
+        assert_eq!(keys(&lint("Subject\nBody right below", None)), [("lint-blank-line", 2)]);
+        let long_line = "word ".repeat(20);
+        let url = format!("https://example.com/{}", "a".repeat(80));
+        let issues = lint(&format!("Subject\n\n{}\n{}", long_line.trim_end(), url), None);
+        assert_eq!(keys(&issues), [("lint-body-long", 3)]);
+        assert!(!issues[0].is_error);
+    }
+
+    #[test]
+    fn conventional_subjects_are_checked_against_the_rules() {
+        assert!(lint("feat(ui): add a button", Some(&rules(&["ui"]))).is_empty());
+        assert!(lint("fix!: drop the old flag", Some(&rules(&[]))).is_empty());
+        assert!(lint("fix(anything): allowed without a scope list", Some(&rules(&[]))).is_empty());
+        assert_eq!(keys(&lint_conventional("Add a button", &rules(&[]))), [("lint-cc-format", 1)]);
+        assert_eq!(keys(&lint_conventional("feat(ui: add a button", &rules(&[]))), [("lint-cc-format", 1)]);
+        assert_eq!(keys(&lint_conventional("feat(): add a button", &rules(&[]))), [("lint-cc-format", 1)]);
+        assert_eq!(keys(&lint_conventional("feature: add a button", &rules(&[]))), [("lint-cc-type", 1)]);
+        assert_eq!(keys(&lint_conventional("feat(core): add a button", &rules(&["ui"]))), [("lint-cc-scope", 1)]);
+    }
+
+    #[test]
+    fn a_note_paragraph_is_not_a_trailer_block() {
+        let message = "Change the timeout\n\nNote: callers relying on the old default need to set it.";
+        assert_eq!(parse_trailers(message), (message.to_string(), Vec::new()));
+        assert_eq!(add_trailer(message, "Signed-off-by", "A <a@example.com>"), format!("{}\n\nSigned-off-by: A <a@example.com>", message));
+    }
+
+    #[test]
+    fn trailers_are_read_from_the_last_paragraph() {
+        let message = "Fix the crash\n\nBody text.\n\nFixes: #12\nReviewed-by: B <b@example.com>\n";
+        let (body, trailers) = parse_trailers(message);
+        assert_eq!(body, "Fix the crash\n\nBody text.");
+        assert_eq!(trailers, [("Fixes".to_string(), "#12".to_string()), ("Reviewed-by".to_string(), "B <b@example.com>".to_string())]);
+        assert_eq!(parse_trailers("Subject only"), ("Subject only".to_string(), Vec::new()));
+    }
+
+    #[test]
+    fn add_trailer_extends_the_existing_block_once() {
+        let message = "Fix the crash\n\nSigned-off-by: A <a@example.com>";
+        let added = add_trailer(message, "Co-authored-by", "B <b@example.com>");
+        assert_eq!(added, "Fix the crash\n\nSigned-off-by: A <a@example.com>\nCo-authored-by: B <b@example.com>");
+        assert_eq!(add_trailer(&added, "signed-off-by", "A <a@example.com>"), added);
+        assert_eq!(add_trailer("Fix the crash", "Signed-off-by", "A <a@example.com>"), "Fix the crash\n\nSigned-off-by: A <a@example.com>");
+    }
+}
@@ -1,9 +1,20 @@
-use git2::{Repository, ResetType, StatusOptions, DiffOptions, RemoteCallbacks, FetchOptions, PushOptions, Cred, StashFlags, ObjectType, Signature};
+use git2::{Repository, ResetType, StatusOptions, DiffOptions, RemoteCallbacks, FetchOptions, PushOptions, Cred, StashFlags, ObjectType, Signature, Sort};
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::io::Write;
 use std::fs::OpenOptions;
+use std::sync::Mutex;
 use chrono::{DateTime, Local};
 
+pub mod diff;
+pub mod export;
 pub mod graph;
+pub mod hooks;
+pub mod message;
+pub mod signing;
+pub mod snapshots;
+
+#[cfg(test)]
+mod tests;
 
 pub const GRAPH_COLORS: [&str; 8] = [
     "#4A90E2", // Blue
@@ -16,6 +27,113 @@ pub const GRAPH_COLORS: [&str; 8] = [
     "#F8A0D8", // Pink
 ];
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct RefLabel {
+    pub name: String,
+    pub kind: String, // "local", "remote", "tag" or "head" (detached HEAD)
+    pub is_head: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubmoduleInfo {
+    pub name: String,
+    pub path: String,
+    pub url: String,
+    pub recorded: Option<String>,    // Commit recorded in the superproject index
+    pub checked_out: Option<String>, // Commit checked out in the submodule working tree
+    pub initialized: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorktreeInfo {
+    pub name: String,
+    pub path: String,
+    pub head: String,           // Checked-out branch, or the short sha when detached
+    pub locked: Option<String>, // Lock reason (empty when locked without one)
+    pub is_main: bool,
+    pub prunable: bool,         // The working directory is gone
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReflogEntry {
+    pub selector: String, // e.g. "HEAD@{2}"
+    pub old_sha: String,
+    pub new_sha: String,
+    pub message: String,
+    pub time: String,
+}
+
+/// What undoing the last GitAmicus operation would do to the ref it touched.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UndoPreview {
+    pub operation: String, // "commit", "amend", "squash" or "reset"
+    pub refname: String,
+    pub current: String,
+    pub target: String,
+    pub restored: Vec<(String, String)>, // (sha, summary) reachable again after the undo
+    pub removed: Vec<(String, String)>,  // (sha, summary) no longer reachable from the ref
+}
+
+/// What a destructive action would throw away, shown before it is confirmed.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct ActionPreview {
+    pub files: Vec<String>,             // Paths whose uncommitted changes are overwritten
+    pub commits: Vec<(String, String)>, // (sha, summary) that become unreachable or are overwritten on the remote
+}
+
+/// A file changed by a commit, with its hunks as diff text, for picking the parts of
+/// a split. Binary files and pure mode changes have a single placeholder hunk.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SplitFile {
+    pub path: String,
+    pub hunks: Vec<String>,
+}
+
+/// One commit of a split: its message and the (path, hunk index) pairs it takes.
+pub type SplitPart = (String, Vec<(String, usize)>);
+
+const PREVIEW_LIMIT: usize = 50;
+const LAST_OPERATION_FILE: &str = "gitamicus-last-operation";
+
+/// How the history view walks the revision graph.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryOptions {
+    pub sort_mode: String, // "topo", "date" or "author-date"
+    pub first_parent: bool,
+    pub simplify_by_decoration: bool,
+    pub hidden_refs: Vec<String>, // Full ref names not used as walk start points
+    pub hide_remotes: bool,
+}
+
+impl Default for HistoryOptions {
+    fn default() -> Self {
+        HistoryOptions { sort_mode: "date".to_string(), first_parent: false, simplify_by_decoration: false, hidden_refs: Vec::new(), hide_remotes: false }
+    }
+}
+
+/// Whitespace handling and context size of file diffs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileDiffOptions {
+    pub ignore_whitespace: bool,
+    pub ignore_whitespace_change: bool,
+    pub ignore_eol: bool, // Whitespace at the end of lines
+    pub context_lines: u32,
+}
+
+impl Default for FileDiffOptions {
+    fn default() -> Self {
+        FileDiffOptions { ignore_whitespace: false, ignore_whitespace_change: false, ignore_eol: false, context_lines: 3 }
+    }
+}
+
+// Walk window of the history modes that need more than the visible rows
+const FULL_WALK_LIMIT: usize = 20000;
+const AUTHOR_WALK_LIMIT: usize = 5000;
+
+// The last history walked in a full-window mode, with the key it was walked for
+type CachedHistory = (String, Vec<(String, String, String, String, Vec<String>)>);
+static HISTORY_CACHE: Mutex<Option<CachedHistory>> = Mutex::new(None);
+
 pub struct GitHandler;
 
 impl GitHandler {
@@ -24,8 +142,11 @@ impl GitHandler {
         Ok(())
     }
 
-    pub fn clone(url: &str, path: &str) -> Result<(), String> {
-        Repository::clone(url, path).map_err(|e| e.message().to_string())?;
+    pub fn clone(url: &str, path: &str, recursive: bool) -> Result<(), String> {
+        let repo = Repository::clone(url, path).map_err(|e| e.message().to_string())?;
+        if recursive {
+            update_submodules(&repo)?;
+        }
         Ok(())
     }
 
@@ -40,6 +161,13 @@ impl GitHandler {
         }
     }
 
+    pub fn get_head_sha(path: &str) -> Result<String, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let head = repo.head().map_err(|e| e.message().to_string())?;
+        let oid = head.target().ok_or("err-head-target-not-found".to_string())?;
+        Ok(oid.to_string())
+    }
+
     pub fn get_branches(path: &str) -> Result<Vec<String>, String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
         let branches = repo.branches(Some(git2::BranchType::Local)).map_err(|e| e.message().to_string())?;
@@ -70,6 +198,73 @@ impl GitHandler {
         Ok(tags.iter().flatten().map(|s| s.to_string()).collect())
     }
 
+    pub fn get_commit_refs(path: &str) -> Result<HashMap<String, Vec<RefLabel>>, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let mut labels: HashMap<String, Vec<RefLabel>> = HashMap::new();
+
+        let head = repo.head().ok();
+        let head_name = head.as_ref().and_then(|h| h.name()).map(|n| n.to_string());
+        if let Some(ref h) = head {
+            if !h.is_branch() {
+                if let Some(oid) = h.target() {
+                    labels.entry(oid.to_string()).or_default().push(RefLabel { name: "HEAD".to_string(), kind: "head".to_string(), is_head: true });
+                }
+            }
+        }
+
+        let references = repo.references().map_err(|e| e.message().to_string())?;
+        for reference in references.flatten() {
+            let full_name = match reference.name() { Some(n) => n.to_string(), None => continue };
+            let kind = if reference.is_branch() {
+                "local"
+            } else if reference.is_remote() {
+                if full_name.ends_with("/HEAD") { continue; }
+                "remote"
+            } else if reference.is_tag() {
+                "tag"
+            } else {
+                continue;
+            };
+            let oid = match reference.peel_to_commit() { Ok(c) => c.id(), Err(_) => continue };
+            let name = reference.shorthand().unwrap_or(&full_name).to_string();
+            let is_head = head_name.as_deref() == Some(full_name.as_str());
+            labels.entry(oid.to_string()).or_default().push(RefLabel { name, kind: kind.to_string(), is_head });
+        }
+
+        let rank = |l: &RefLabel| match l.kind.as_str() { "head" => 0, "local" => 1, "remote" => 2, _ => 3 };
+        for list in labels.values_mut() {
+            list.sort_by(|a, b| b.is_head.cmp(&a.is_head).then(rank(a).cmp(&rank(b))).then(a.name.cmp(&b.name)));
+        }
+        Ok(labels)
+    }
+
+    /// Branches and tags whose history contains the given commit.
+    pub fn get_containing_refs(path: &str, revision: &str) -> Result<Vec<RefLabel>, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let target = repo.revparse_single(revision).map_err(|e| e.message().to_string())?.id();
+        let references = repo.references().map_err(|e| e.message().to_string())?;
+        let mut labels = Vec::new();
+        for reference in references.flatten() {
+            let Some(full_name) = reference.name().map(|n| n.to_string()) else { continue };
+            let kind = if reference.is_branch() {
+                "local"
+            } else if reference.is_remote() && !full_name.ends_with("/HEAD") {
+                "remote"
+            } else if reference.is_tag() {
+                "tag"
+            } else {
+                continue;
+            };
+            let Ok(tip) = reference.peel_to_commit() else { continue };
+            if tip.id() == target || repo.graph_descendant_of(tip.id(), target).unwrap_or(false) {
+                let name = reference.shorthand().unwrap_or(&full_name).to_string();
+                labels.push(RefLabel { name, kind: kind.to_string(), is_head: false });
+            }
+        }
+        labels.sort_by(|a, b| a.kind.cmp(&b.kind).then(a.name.cmp(&b.name)));
+        Ok(labels)
+    }
+
     #[allow(dead_code)]
     pub fn create_tag(path: &str, name: &str) -> Result<(), String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
@@ -99,17 +294,23 @@ impl GitHandler {
     pub fn reset_hard(path: &str, revision: &str) -> Result<(), String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
         let obj = repo.revparse_single(revision).map_err(|e| e.message().to_string())?;
+        snapshots::create_snapshot(&repo, "reset")?;
+        let before = head_target(&repo);
         repo.reset(&obj, ResetType::Hard, None).map_err(|e| e.message().to_string())?;
+        record_operation(&repo, "reset", before);
         Ok(())
     }
 
     pub fn checkout_commit(path: &str, revision: &str) -> Result<(), String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
         let obj = repo.revparse_single(revision).map_err(|e| e.message().to_string())?;
+        snapshots::create_snapshot(&repo, "checkout")?;
+        let old_head = repo.head().ok().and_then(|h| h.target()).unwrap_or_else(git2::Oid::zero);
         repo.set_head_detached(obj.id()).map_err(|e| e.message().to_string())?;
         let mut checkout_opts = git2::build::CheckoutBuilder::new();
         checkout_opts.force();
         repo.checkout_head(Some(&mut checkout_opts)).map_err(|e| e.message().to_string())?;
+        run_post_checkout(&repo, old_head);
         Ok(())
     }
 
@@ -120,24 +321,202 @@ impl GitHandler {
         Ok(())
     }
 
-    pub fn amend_head(path: &str, new_msg: Option<&str>, new_author: Option<(&str, &str)>) -> Result<(), String> {
+    /// Rewrites the message or author of `sha` and replays the commits after it on
+    /// top, keeping their trees. Returns how many commits were rewritten.
+    pub fn rewrite_commit(path: &str, sha: &str, new_msg: Option<&str>, new_author: Option<(&str, &str)>) -> Result<usize, String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
-        let head = repo.head().map_err(|e| e.message().to_string())?;
-        let oid = head.target().ok_or("err-head-target-not-found".to_string())?;
-        let head_commit = repo.find_commit(oid).map_err(|e| e.message().to_string())?;
-        
-        let tree = head_commit.tree().map_err(|e| e.message().to_string())?;
+        let commits = rewrite_range(&repo, sha)?;
+        let before = head_target(&repo);
+        let sign = signing::signing_enabled(&repo);
+        let new_sig = match new_author {
+            Some((name, email)) => Some(Signature::now(name, email).map_err(|e| e.message().to_string())?),
+            None => None,
+        };
 
-        if let Some((name, email)) = new_author {
-            let new_sig = Signature::now(name, email).map_err(|e| e.message().to_string())?;
-            head_commit.amend(Some("HEAD"), Some(&new_sig), Some(&new_sig), None, new_msg, Some(&tree))
-                .map_err(|e| e.message().to_string())?;
-        } else {
-            head_commit.amend(Some("HEAD"), None, None, None, new_msg, Some(&tree))
-                .map_err(|e| e.message().to_string())?;
+        let target = &commits[0];
+        let (author, committer) = match &new_sig {
+            Some(sig) => (sig.clone(), sig.clone()),
+            None => (target.author().to_owned(), target.committer().to_owned()),
+        };
+        let message = new_msg.unwrap_or(target.message().unwrap_or_default());
+        let tree = target.tree().map_err(|e| e.message().to_string())?;
+        let parents: Vec<git2::Commit> = target.parents().collect();
+        let parents_ref: Vec<&git2::Commit> = parents.iter().collect();
+        let oid = create_commit_object(&repo, &author, &committer, message, &tree, &parents_ref, sign)?;
+        let rewritten = repo.find_commit(oid).map_err(|e| e.message().to_string())?;
+
+        let new_tip = replay(&repo, &commits[1..], rewritten, sign)?;
+        move_head(&repo, new_tip.id(), &format!("rewrite: {}", target.summary().unwrap_or_default()))?;
+        record_operation(&repo, "amend", before);
+        Ok(commits.len())
+    }
+
+    /// How many commits `rewrite_commit` would recreate for `sha`, or why it refuses.
+    pub fn get_rewrite_preview(path: &str, sha: &str) -> Result<usize, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        rewrite_range(&repo, sha).map(|commits| commits.len())
+    }
+
+    /// The changes of `sha` grouped by file and hunk, as offered by the split dialog.
+    pub fn get_split_files(path: &str, sha: &str) -> Result<Vec<SplitFile>, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let commit = repo.revparse_single(sha).and_then(|o| o.peel_to_commit()).map_err(|e| e.message().to_string())?;
+        let diff = split_diff(&repo, &commit)?;
+        let mut files = Vec::new();
+        for (idx, delta) in diff.deltas().enumerate() {
+            let path = delta_path(&delta);
+            let mut hunks = Vec::new();
+            if let Ok(Some(patch)) = git2::Patch::from_diff(&diff, idx) {
+                for h in 0..patch.num_hunks() {
+                    let Ok((hunk, line_count)) = patch.hunk(h) else { continue };
+                    let mut text = String::from_utf8_lossy(hunk.header()).to_string();
+                    for l in 0..line_count {
+                        if let Ok(line) = patch.line_in_hunk(h, l) {
+                            text.push(line.origin());
+                            text.push_str(&String::from_utf8_lossy(line.content()));
+                        }
+                    }
+                    hunks.push(text.trim_end().to_string());
+                }
+            }
+            if hunks.is_empty() {
+                hunks.push(if delta.flags().is_binary() { "Binary file".to_string() } else { format!("{:?}", delta.status()) });
+            }
+            files.push(SplitFile { path, hunks });
         }
-        
-        Ok(())
+        Ok(files)
+    }
+
+    /// Replaces `sha` with one commit per part, each adding the hunks it picked to the
+    /// previous one. Hunks no part picked go into a last commit with `rest_message`.
+    /// The commits after `sha` are replayed on top. Returns the number of new commits.
+    pub fn split_commit(path: &str, sha: &str, parts: &[SplitPart], rest_message: &str) -> Result<usize, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let commits = rewrite_range(&repo, sha)?;
+        let before = head_target(&repo);
+        let target = &commits[0];
+        let sign = signing::signing_enabled(&repo);
+        let diff = split_diff(&repo, target)?;
+        let parent_tree = match target.parent(0) {
+            Ok(parent) => parent.tree().map_err(|e| e.message().to_string())?,
+            Err(_) => empty_tree(&repo)?,
+        };
+        let target_tree = target.tree().map_err(|e| e.message().to_string())?;
+
+        // Each part's tree is the parent plus every hunk picked so far
+        let mut planned: Vec<(git2::Tree, &str)> = Vec::new();
+        let mut selected: HashSet<(String, usize)> = HashSet::new();
+        for (message, hunks) in parts {
+            selected.extend(hunks.iter().cloned());
+            let tree = apply_hunks(&repo, &parent_tree, &diff, &selected)?;
+            let previous = planned.last().map(|(t, _)| t.id()).unwrap_or(parent_tree.id());
+            if tree.id() == previous || message.trim().is_empty() {
+                return Err("err-split-empty-part".to_string());
+            }
+            planned.push((tree, message));
+        }
+        if planned.last().map(|(t, _)| t.id()) != Some(target_tree.id()) {
+            if rest_message.trim().is_empty() {
+                return Err("err-split-empty-part".to_string());
+            }
+            planned.push((target_tree, rest_message));
+        }
+
+        let mut tip = target.parents().next();
+        for (tree, message) in &planned {
+            let parents: Vec<&git2::Commit> = tip.iter().collect();
+            let oid = create_commit_object(&repo, &target.author(), &target.committer(), message, tree, &parents, sign)?;
+            tip = Some(repo.find_commit(oid).map_err(|e| e.message().to_string())?);
+        }
+        let split_tip = tip.ok_or("err-split-empty-part".to_string())?;
+        let new_tip = replay(&repo, &commits[1..], split_tip, sign)?;
+        move_head(&repo, new_tip.id(), &format!("split: {}", target.summary().unwrap_or_default()))?;
+        record_operation(&repo, "split", before);
+        Ok(planned.len())
+    }
+
+    /// Commits the staged changes as `fixup! <summary>` of `sha`, to be folded into it
+    /// by `autosquash`.
+    pub fn create_fixup_commit(path: &str, sha: &str, sign: bool, verify: bool) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let target = repo.revparse_single(sha).and_then(|o| o.peel_to_commit()).map_err(|e| e.message().to_string())?;
+        let head_tree = repo.head().ok().and_then(|h| h.peel_to_tree().ok()).map(|t| t.id());
+        let index_tree = repo.index().and_then(|mut i| i.write_tree()).map_err(|e| e.message().to_string())?;
+        if head_tree == Some(index_tree) {
+            return Err("err-nothing-staged".to_string());
+        }
+        let message = format!("fixup! {}", target.summary().unwrap_or_default());
+        Self::create_commit(path, &message, sign, verify, false)
+    }
+
+    /// Folds every `fixup!` and `squash!` commit after the upstream into the commit it
+    /// names and replays the other commits on top. Returns how many commits were folded.
+    pub fn autosquash(path: &str) -> Result<usize, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let head = repo.head().map_err(|_| "err-head-not-found".to_string())?;
+        let head_commit = head.peel_to_commit().map_err(|e| e.message().to_string())?;
+
+        let mut revwalk = repo.revwalk().map_err(|e| e.message().to_string())?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE).map_err(|e| e.message().to_string())?;
+        revwalk.push(head_commit.id()).map_err(|e| e.message().to_string())?;
+        if let Some((_, upstream_oid)) = upstream_of(&repo, &head) {
+            revwalk.hide(upstream_oid).map_err(|e| e.message().to_string())?;
+        }
+        let candidates: Vec<git2::Commit> = revwalk.flatten().filter_map(|oid| repo.find_commit(oid).ok()).collect();
+
+        // Target commit -> the fixup/squash commits folded into it, oldest first
+        let mut folds: HashMap<git2::Oid, Vec<git2::Oid>> = HashMap::new();
+        let mut folded: HashSet<git2::Oid> = HashSet::new();
+        for (i, commit) in candidates.iter().enumerate() {
+            let Some(subject) = autosquash_subject(commit.summary().unwrap_or_default()) else { continue };
+            let older = || candidates[..i].iter().filter(|c| !folded.contains(&c.id()));
+            let target = older().find(|c| c.summary() == Some(subject))
+                .or_else(|| older().find(|c| subject.len() >= 4 && c.id().to_string().starts_with(subject)))
+                .or_else(|| older().find(|c| c.summary().unwrap_or_default().starts_with(subject)));
+            if let Some(target) = target {
+                folds.entry(target.id()).or_default().push(commit.id());
+                folded.insert(commit.id());
+            }
+        }
+        let Some(oldest_target) = candidates.iter().find(|c| folds.contains_key(&c.id())) else {
+            return Err("err-autosquash-nothing".to_string());
+        };
+
+        let commits = rewrite_range(&repo, &oldest_target.id().to_string())?;
+        let sign = signing::signing_enabled(&repo);
+        let mut tip = commits[0].parents().next();
+        let mut tree = match &tip {
+            Some(parent) => parent.tree().map_err(|e| e.message().to_string())?,
+            None => empty_tree(&repo)?,
+        };
+        for commit in commits.iter().filter(|c| !folded.contains(&c.id())) {
+            let mut message = commit.message().unwrap_or_default().trim_end().to_string();
+            tree = pick_tree(&repo, commit, &tree)?;
+            for fixup_oid in folds.get(&commit.id()).into_iter().flatten() {
+                let fixup = repo.find_commit(*fixup_oid).map_err(|e| e.message().to_string())?;
+                tree = pick_tree(&repo, &fixup, &tree)?;
+                // A squash! keeps its body, a fixup! is dropped from the message
+                let fixup_message = fixup.message().unwrap_or_default();
+                let (subject, body) = message::split(fixup_message);
+                if subject.starts_with("squash! ") && !body.trim().is_empty() {
+                    message = format!("{}\n\n{}", message, body.trim_end());
+                }
+            }
+            let parents: Vec<&git2::Commit> = tip.iter().collect();
+            let oid = create_commit_object(&repo, &commit.author(), &commit.committer(), &message, &tree, &parents, sign)?;
+            tip = Some(repo.find_commit(oid).map_err(|e| e.message().to_string())?);
+        }
+
+        let new_tip = tip.ok_or("err-head-not-found".to_string())?;
+        if new_tip.tree_id() != head_commit.tree_id() {
+            // The reordered changes can end up in a different tree; keep local changes safe
+            let mut checkout = git2::build::CheckoutBuilder::new();
+            checkout.safe();
+            repo.checkout_tree(new_tip.as_object(), Some(&mut checkout)).map_err(|e| e.message().to_string())?;
+        }
+        move_head(&repo, new_tip.id(), "autosquash")?;
+        record_operation(&repo, "autosquash", head_commit.id());
+        Ok(folded.len())
     }
 
     pub fn squash_parent(path: &str) -> Result<(), String> {
@@ -150,6 +529,8 @@ impl GitHandler {
         }
         let parent_commit = head_commit.parent(0).map_err(|e| e.message().to_string())?;
 
+        // The soft reset below is a ref move of its own; undo has to go back past it
+        let before = head_commit.id();
         repo.reset(parent_commit.as_object(), ResetType::Soft, None)
             .map_err(|e| e.message().to_string())?;
 
@@ -160,7 +541,8 @@ impl GitHandler {
         let grandparents: Vec<_> = parent_commit.parents().collect();
         let grandparents_ref: Vec<&_> = grandparents.iter().collect();
         let message = head_commit.message().unwrap_or_default();
-        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &grandparents_ref).map_err(|e| e.message().to_string())?;
+        write_commit(&repo, &sig, &sig, message, &tree, &grandparents_ref, signing::signing_enabled(&repo))?;
+        record_operation(&repo, "squash", before);
         Ok(())
     }
 
@@ -213,6 +595,58 @@ impl GitHandler {
         Ok((author, committer, message, sha, parents))
     }
 
+    /// Every ref with its target and where HEAD points; changes whenever a ref moves.
+    pub fn get_ref_state(path: &str) -> String {
+        Repository::open(path).map(|repo| ref_state(&repo)).unwrap_or_default()
+    }
+
+    pub fn get_signature_info(path: &str, revision: &str) -> Result<signing::SignatureInfo, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let obj = repo.revparse_single(revision).map_err(|e| e.message().to_string())?;
+        Ok(signing::verify_commit(&repo, obj.id(), &signing::trust_state(&repo)))
+    }
+
+    pub fn get_signature_infos(path: &str, shas: &[String]) -> Result<HashMap<String, signing::SignatureInfo>, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let trust = signing::trust_state(&repo);
+        let mut infos = HashMap::new();
+        for sha in shas.iter() {
+            if let Ok(oid) = git2::Oid::from_str(sha) {
+                infos.insert(sha.clone(), signing::verify_commit(&repo, oid, &trust));
+            }
+        }
+        Ok(infos)
+    }
+
+    /// Returns the commits among `shas` that are reachable from one of the given local branches.
+    pub fn get_commits_on_branches(path: &str, branches: &[String], shas: &[String]) -> Result<HashSet<String>, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let tips: Vec<git2::Oid> = branches.iter()
+            .filter_map(|b| repo.find_branch(b, git2::BranchType::Local).ok())
+            .filter_map(|b| b.get().target())
+            .collect();
+
+        let mut reachable = HashSet::new();
+        if tips.is_empty() {
+            return Ok(reachable);
+        }
+        // One walk from all tips instead of a merge-base search per commit
+        let wanted: HashSet<git2::Oid> = shas.iter().filter_map(|sha| git2::Oid::from_str(sha).ok()).collect();
+        let mut revwalk = repo.revwalk().map_err(|e| e.message().to_string())?;
+        for tip in tips {
+            revwalk.push(tip).map_err(|e| e.message().to_string())?;
+        }
+        for oid in revwalk.flatten() {
+            if wanted.contains(&oid) {
+                reachable.insert(oid.to_string());
+                if reachable.len() == wanted.len() {
+                    break;
+                }
+            }
+        }
+        Ok(reachable)
+    }
+
     pub fn get_commit_files(path: &str, revision: &str) -> Result<Vec<String>, String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
         let obj = repo.revparse_single(revision).map_err(|e| e.message().to_string())?;
@@ -234,7 +668,7 @@ impl GitHandler {
         Ok(files)
     }
 
-    pub fn get_commit_file_diff(path: &str, revision: &str, file_path: &str) -> Result<String, String> {
+    pub fn get_commit_file_diff(path: &str, revision: &str, file_path: &str, options: &FileDiffOptions) -> Result<String, String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
         let obj = repo.revparse_single(revision).map_err(|e| e.message().to_string())?;
         let commit = repo.find_commit(obj.id()).map_err(|e| e.message().to_string())?;
@@ -244,27 +678,31 @@ impl GitHandler {
         } else {
             None
         };
-        let mut opts = DiffOptions::new();
-        opts.pathspec(file_path);
+
+        // Submodules are stored as commit entries (gitlinks) in the tree
+        let gitlink = |t: Option<&git2::Tree>| t
+            .and_then(|t| t.get_path(std::path::Path::new(file_path)).ok())
+            .filter(|e| e.kind() == Some(ObjectType::Commit))
+            .map(|e| e.id());
+        let (old_link, new_link) = (gitlink(parent_tree.as_ref()), gitlink(Some(&tree)));
+        if old_link.is_some() || new_link.is_some() {
+            return Ok(submodule_diff(&repo, file_path, old_link, new_link));
+        }
+        let mut opts = file_diff_options(file_path, options);
         let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts)).map_err(|e| e.message().to_string())?;
-        let mut diff_text = String::new();
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-            let prefix = match line.origin() { '+' => "+", '-' => "-", ' ' => " ", _ => "" };
-            diff_text.push_str(prefix);
-            diff_text.push_str(std::str::from_utf8(line.content()).unwrap_or(""));
-            true
-        }).map_err(|e| e.message().to_string())?;
-        Ok(diff_text)
+        diff_to_text(&diff)
     }
 
     pub fn checkout_branch(path: &str, branch_name: &str) -> Result<(), String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
         let (object, reference) = repo.revparse_ext(branch_name).map_err(|e| e.message().to_string())?;
+        let old_head = repo.head().ok().and_then(|h| h.target()).unwrap_or_else(git2::Oid::zero);
         repo.checkout_tree(&object, None).map_err(|e| e.message().to_string())?;
         match reference {
             Some(gref) => repo.set_head(gref.name().ok_or("err-invalid-ref-name".to_string())?),
             None => repo.set_head_detached(object.id()),
         }.map_err(|e| e.message().to_string())?;
+        run_post_checkout(&repo, old_head);
         Ok(())
     }
 
@@ -284,8 +722,25 @@ impl GitHandler {
         Ok(())
     }
 
+    pub fn delete_tag(path: &str, name: &str) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        repo.tag_delete(name).map_err(|e| e.message().to_string())?;
+        Ok(())
+    }
+
+    pub fn track_remote_branch(path: &str, remote_branch: &str) -> Result<String, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let remote = repo.find_branch(remote_branch, git2::BranchType::Remote).map_err(|e| e.message().to_string())?;
+        let commit = remote.get().peel_to_commit().map_err(|e| e.message().to_string())?;
+        let local_name = remote_branch.split_once('/').map(|(_, b)| b).unwrap_or(remote_branch).to_string();
+        let mut local = repo.branch(&local_name, &commit, false).map_err(|e| e.message().to_string())?;
+        local.set_upstream(Some(remote_branch)).map_err(|e| e.message().to_string())?;
+        Ok(local_name)
+    }
+
     pub fn discard_changes(path: &str, file: &str) -> Result<(), String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        snapshots::create_snapshot(&repo, "discard")?;
         let mut checkout_opts = git2::build::CheckoutBuilder::new();
         checkout_opts.path(std::path::Path::new(file));
         checkout_opts.force();
@@ -293,20 +748,105 @@ impl GitHandler {
         Ok(())
     }
 
-    pub fn push(path: &str, user: &str, token: &str, force: bool) -> Result<(), String> {
+    pub fn preview_reset_hard(path: &str, revision: &str) -> Result<ActionPreview, String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
-        let mut remote = repo.find_remote("origin").map_err(|e| e.message().to_string())?;
+        let target = repo.revparse_single(revision).and_then(|o| o.peel_to_commit()).map_err(|e| e.message().to_string())?;
+        let head = repo.head().map_err(|e| e.message().to_string())?;
+        let head_oid = head.peel_to_commit().map_err(|e| e.message().to_string())?.id();
+
+        // A hard reset leaves untracked files alone, everything else is overwritten
+        let mut options = StatusOptions::new();
+        options.include_untracked(false);
+        let statuses = repo.statuses(Some(&mut options)).map_err(|e| e.message().to_string())?;
+        let files = statuses.iter().filter_map(|entry| entry.path().map(|p| p.to_string())).collect();
+
+        let moved_ref = if repo.head_detached().unwrap_or(false) { None } else { head.name().map(|n| n.to_string()) };
+        let commits = unreachable_commits(&repo, head_oid, moved_ref.as_deref(), &[target.id()]);
+        Ok(ActionPreview { files, commits })
+    }
+
+    pub fn preview_discard(path: &str, file: &str) -> Result<ActionPreview, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.pathspec(file);
+        let diff = repo.diff_index_to_workdir(None, Some(&mut diff_opts)).map_err(|e| e.message().to_string())?;
+        let stats = diff.stats().map_err(|e| e.message().to_string())?;
+        let files = if stats.files_changed() > 0 {
+            vec![format!("{} (+{} -{})", file, stats.insertions(), stats.deletions())]
+        } else {
+            vec![file.to_string()]
+        };
+        Ok(ActionPreview { files, commits: Vec::new() })
+    }
+
+    pub fn preview_delete_branch(path: &str, name: &str) -> Result<ActionPreview, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let branch = repo.find_branch(name, git2::BranchType::Local).map_err(|e| e.message().to_string())?;
+        let tip = branch.get().peel_to_commit().map_err(|e| e.message().to_string())?.id();
+        // A detached HEAD keeps its commits alive without being a ref
+        let keep: Vec<git2::Oid> = if repo.head_detached().unwrap_or(false) {
+            repo.head().ok().and_then(|h| h.target()).into_iter().collect()
+        } else {
+            Vec::new()
+        };
+        let commits = unreachable_commits(&repo, tip, branch.get().name(), &keep);
+        Ok(ActionPreview { files: Vec::new(), commits })
+    }
+
+    /// Commits on the remote branch that a force push of HEAD would drop,
+    /// as far as the remote-tracking ref from the last fetch knows.
+    pub fn preview_force_push(path: &str) -> Result<ActionPreview, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let head = repo.head().map_err(|e| e.message().to_string())?;
+        let head_oid = head.peel_to_commit().map_err(|e| e.message().to_string())?.id();
+        let commits = match remote_tracking_oid(&repo, &head) {
+            Some(remote_oid) => commits_between(&repo, remote_oid, head_oid),
+            None => Vec::new(),
+        };
+        Ok(ActionPreview { files: Vec::new(), commits })
+    }
+
+    /// Pushes the current branch to its upstream, or to the same name on origin when it
+    /// has none. `mode` is "normal", "lease" (force, but only while the remote still has
+    /// what our remote-tracking ref recorded) or "force".
+    /// The pre-push hook runs unless `verify` is off.
+    pub fn push(path: &str, user: &str, token: &str, mode: &str, verify: bool) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let head = repo.head().map_err(|e| e.message().to_string())?;
+        let local_ref = head.name().ok_or("err-invalid-ref-name".to_string())?.to_string();
+        let (remote_name, refspec) = push_destination(&repo, &local_ref);
+        let mut remote = repo.find_remote(&remote_name).map_err(|e| e.message().to_string())?;
+        let expected = remote_tracking_oid(&repo, &head).unwrap_or_else(git2::Oid::zero);
+
+        if verify {
+            let local = head.target().unwrap_or_else(git2::Oid::zero);
+            let updates = format!("{} {} {} {}\n", local_ref, local, refspec, expected);
+            hooks::run(&repo, "pre-push", &[&remote_name, remote.url().unwrap_or_default()], &updates)?;
+        }
+
+        let lease_broken = std::cell::Cell::new(false);
         let mut callbacks = RemoteCallbacks::new();
         callbacks.credentials(|_url, _username_from_url, _allowed_types| {
             Cred::userpass_plaintext(user, token)
         });
+        if mode == "lease" {
+            // Called with what the remote has right now, before anything is sent
+            callbacks.push_negotiation(|updates| {
+                if updates.iter().any(|u| u.dst_refname() == Some(refspec.as_str()) && u.src() != expected) {
+                    lease_broken.set(true);
+                    return Err(git2::Error::from_str("err-push-lease"));
+                }
+                Ok(())
+            });
+        }
         let mut options = PushOptions::new();
         options.remote_callbacks(callbacks);
-        let head = repo.head().map_err(|e| e.message().to_string())?;
-        let refspec = head.name().ok_or("err-invalid-ref-name".to_string())?;
-        let remote_ref = if force { format!("+{}", refspec) } else { refspec.to_string() };
-        remote.push(&[format!("{}:{}", remote_ref, refspec)], Some(&mut options)).map_err(|e| e.message().to_string())?;
-        Ok(())
+        let local_ref = if mode == "normal" { local_ref } else { format!("+{}", local_ref) };
+        let result = remote.push(&[format!("{}:{}", local_ref, refspec)], Some(&mut options));
+        if lease_broken.get() {
+            return Err("err-push-lease".to_string());
+        }
+        result.map_err(|e| e.message().to_string())
     }
 
     pub fn pull(path: &str, user: &str, token: &str) -> Result<(), String> {
@@ -337,25 +877,40 @@ impl GitHandler {
         Ok(())
     }
 
-    pub fn get_latest_commits_full(repo_path: &str, limit: usize, all_branches: bool) -> Result<Vec<(String, String, String, String, Vec<String>)>, String> {
+    pub fn get_latest_commits_full(repo_path: &str, limit: usize, all_branches: bool, options: &HistoryOptions) -> Result<Vec<(String, String, String, String, Vec<String>)>, String> {
         let repo = Repository::open(repo_path).map_err(|e| e.message().to_string())?;
         let mut revwalk = repo.revwalk().map_err(|e| e.message().to_string())?;
         if all_branches {
-            revwalk.push_glob("refs/heads/*").ok();
-            revwalk.push_glob("refs/remotes/*").ok();
-            revwalk.push_glob("refs/tags/*").ok();
+            let references = repo.references().map_err(|e| e.message().to_string())?;
+            for reference in references.flatten() {
+                let Some(name) = reference.name() else { continue };
+                let kind_visible = reference.is_branch() || reference.is_tag() || (reference.is_remote() && !options.hide_remotes);
+                if !kind_visible || options.hidden_refs.iter().any(|h| h == name) {
+                    continue;
+                }
+                if let Ok(commit) = reference.peel_to_commit() {
+                    revwalk.push(commit.id()).ok();
+                }
+            }
         } else {
             revwalk.push_head().ok();
         }
-        let mut commits = Vec::new();
-        for oid in revwalk.take(limit) {
-            let oid = oid.map_err(|e| format!("Failed to walk revision: {}", e))?;
-            let commit = repo.find_commit(oid).map_err(|e| format!("Failed to find commit: {}", e))?;
-            let parents: Vec<String> = commit.parents().map(|p| p.id().to_string()).collect();
-            let time = DateTime::from_timestamp(commit.time().seconds(), 0).map(|dt| dt.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
-            commits.push((commit.id().to_string(), commit.summary().unwrap_or("").to_string(), commit.author().name().unwrap_or("").to_string(), time, parents));
+        walk_history(repo_path, &repo, revwalk, if all_branches { "all" } else { "HEAD" }, limit, options)
+    }
+
+    pub fn get_commits_in_range(repo_path: &str, range: &str, limit: usize, all_branches: bool, options: &HistoryOptions) -> Result<Vec<(String, String, String, String, Vec<String>)>, String> {
+        if range.trim().is_empty() {
+            return Self::get_latest_commits_full(repo_path, limit, all_branches, options);
+        }
+        let repo = Repository::open(repo_path).map_err(|e| e.message().to_string())?;
+        let mut revwalk = repo.revwalk().map_err(|e| e.message().to_string())?;
+        if range.contains("..") {
+            revwalk.push_range(range.trim()).map_err(|e| e.message().to_string())?;
+        } else {
+            let obj = repo.revparse_single(range.trim()).map_err(|e| e.message().to_string())?;
+            revwalk.push(obj.id()).map_err(|e| e.message().to_string())?;
         }
-        Ok(commits)
+        walk_history(repo_path, &repo, revwalk, range.trim(), limit, options)
     }
 
     pub fn get_status(path: &str) -> Result<Vec<(String, String)>, String> {
@@ -376,6 +931,232 @@ impl GitHandler {
         Ok(changed_files)
     }
 
+    pub fn get_submodules(path: &str) -> Result<Vec<SubmoduleInfo>, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let submodules = repo.submodules().map_err(|e| e.message().to_string())?;
+        Ok(submodules.iter().map(|sm| SubmoduleInfo {
+            name: sm.name().unwrap_or_default().to_string(),
+            path: sm.path().to_string_lossy().to_string(),
+            url: sm.url().unwrap_or_default().to_string(),
+            recorded: sm.index_id().or(sm.head_id()).map(|id| id.to_string()),
+            checked_out: sm.workdir_id().map(|id| id.to_string()),
+            initialized: sm.open().is_ok(),
+        }).collect())
+    }
+
+    pub fn submodule_init(path: &str, name: &str) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let mut sm = repo.find_submodule(name).map_err(|e| e.message().to_string())?;
+        sm.init(false).map_err(|e| e.message().to_string())?;
+        Ok(())
+    }
+
+    /// Checks out the recorded commit, initializing the submodule and its nested submodules if needed.
+    pub fn submodule_update(path: &str, name: &str) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let mut sm = repo.find_submodule(name).map_err(|e| e.message().to_string())?;
+        sm.update(true, None).map_err(|e| e.message().to_string())?;
+        let sub_repo = sm.open().map_err(|e| e.message().to_string())?;
+        update_submodules(&sub_repo)
+    }
+
+    /// Copies the URL from .gitmodules into the repository config and the submodule's origin.
+    pub fn submodule_sync(path: &str, name: &str) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let mut sm = repo.find_submodule(name).map_err(|e| e.message().to_string())?;
+        sm.sync().map_err(|e| e.message().to_string())?;
+        Ok(())
+    }
+
+    /// Lists the main working tree followed by all linked worktrees.
+    pub fn get_worktrees(path: &str) -> Result<Vec<WorktreeInfo>, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let common_dir = common_dir(&repo);
+        let main_path = common_dir.parent().map(|p| p.to_path_buf()).unwrap_or_default();
+        let head_name = |p: &std::path::Path| Repository::open(p).ok()
+            .and_then(|r| r.head().ok().map(|h| if h.is_branch() { h.shorthand().unwrap_or_default().to_string() } else { h.target().map(|t| t.to_string()[..7].to_string()).unwrap_or_default() }))
+            .unwrap_or_default();
+
+        let mut worktrees = vec![WorktreeInfo {
+            name: main_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            path: main_path.display().to_string(),
+            head: head_name(&main_path),
+            locked: None,
+            is_main: true,
+            prunable: false,
+        }];
+
+        let names = repo.worktrees().map_err(|e| e.message().to_string())?;
+        for name in names.iter().flatten() {
+            let Ok(wt) = repo.find_worktree(name) else { continue };
+            let locked = match wt.is_locked() {
+                Ok(git2::WorktreeLockStatus::Locked(reason)) => Some(reason.unwrap_or_default()),
+                _ => None,
+            };
+            worktrees.push(WorktreeInfo {
+                name: name.to_string(),
+                path: wt.path().display().to_string().trim_end_matches('/').to_string(),
+                head: head_name(wt.path()),
+                locked,
+                is_main: false,
+                prunable: wt.validate().is_err(),
+            });
+        }
+        Ok(worktrees)
+    }
+
+    /// Adds a worktree named after the last component of `target`. A local branch is
+    /// checked out as is; any other revision gets a new branch with the worktree's name.
+    pub fn create_worktree(path: &str, revision: &str, target: &str) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let target_path = std::path::Path::new(target);
+        let name = target_path.file_name().and_then(|n| n.to_str()).ok_or("err-worktree-path".to_string())?;
+
+        let (mut branch, created) = match repo.find_branch(revision, git2::BranchType::Local) {
+            Ok(branch) => (branch, false),
+            Err(_) => {
+                let obj = repo.revparse_single(revision).map_err(|e| e.message().to_string())?;
+                let commit = obj.peel_to_commit().map_err(|e| e.message().to_string())?;
+                (repo.branch(name, &commit, false).map_err(|e| e.message().to_string())?, true)
+            }
+        };
+        let mut options = git2::WorktreeAddOptions::new();
+        options.reference(Some(branch.get()));
+        let result = repo.worktree(name, target_path, Some(&options)).map(|_| ());
+        if result.is_err() && created {
+            // Do not leave the branch made for the worktree behind
+            let _ = branch.delete();
+        }
+        result.map_err(|e| e.message().to_string())
+    }
+
+    /// Removes the administrative data of worktrees whose directory no longer exists.
+    pub fn prune_worktrees(path: &str) -> Result<usize, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let names = repo.worktrees().map_err(|e| e.message().to_string())?;
+        let mut pruned = 0;
+        for name in names.iter().flatten() {
+            let Ok(wt) = repo.find_worktree(name) else { continue };
+            if wt.is_prunable(None).unwrap_or(false) {
+                wt.prune(None).map_err(|e| e.message().to_string())?;
+                pruned += 1;
+            }
+        }
+        Ok(pruned)
+    }
+
+    pub fn lock_worktree(path: &str, name: &str, reason: Option<&str>) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let wt = repo.find_worktree(name).map_err(|e| e.message().to_string())?;
+        wt.lock(reason).map_err(|e| e.message().to_string())?;
+        Ok(())
+    }
+
+    pub fn unlock_worktree(path: &str, name: &str) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let wt = repo.find_worktree(name).map_err(|e| e.message().to_string())?;
+        wt.unlock().map_err(|e| e.message().to_string())?;
+        Ok(())
+    }
+
+    pub fn get_reflog(path: &str, refname: &str) -> Result<Vec<ReflogEntry>, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let reflog = repo.reflog(refname).map_err(|e| e.message().to_string())?;
+        let short_name = refname.strip_prefix("refs/heads/").unwrap_or(refname);
+        Ok(reflog.iter().enumerate().map(|(i, entry)| ReflogEntry {
+            selector: format!("{}@{{{}}}", short_name, i),
+            old_sha: entry.id_old().to_string(),
+            new_sha: entry.id_new().to_string(),
+            message: entry.message().unwrap_or_default().to_string(),
+            time: DateTime::from_timestamp(entry.committer().when().seconds(), 0).map(|dt| dt.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default(),
+        }).collect())
+    }
+
+    /// Looks up the ref moved by the last GitAmicus operation and where it pointed before.
+    pub fn get_undo_preview(path: &str) -> Result<UndoPreview, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let record = std::fs::read_to_string(repo.path().join(LAST_OPERATION_FILE)).map_err(|_| "err-undo-nothing".to_string())?;
+        let mut lines = record.lines();
+        let (Some(refname), Some(recorded), Some(before), Some(operation)) = (lines.next(), lines.next(), lines.next(), lines.next()) else {
+            return Err("err-undo-nothing".to_string());
+        };
+
+        let current = repo.refname_to_id(refname).map_err(|e| e.message().to_string())?;
+        if current.to_string() != recorded {
+            return Err("err-undo-moved".to_string());
+        }
+        let target = git2::Oid::from_str(before).map_err(|_| "err-undo-nothing".to_string())?;
+        if target.is_zero() || repo.find_commit(target).is_err() {
+            return Err("err-undo-nothing".to_string());
+        }
+
+        Ok(UndoPreview {
+            operation: operation.to_string(),
+            refname: refname.to_string(),
+            current: current.to_string(),
+            target: target.to_string(),
+            restored: commits_between(&repo, target, current),
+            removed: commits_between(&repo, current, target),
+        })
+    }
+
+    pub fn undo_last_operation(path: &str) -> Result<(), String> {
+        let preview = Self::get_undo_preview(path)?;
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let target = repo.find_commit(git2::Oid::from_str(&preview.target).map_err(|e| e.message().to_string())?).map_err(|e| e.message().to_string())?;
+
+        let head = repo.find_reference("HEAD").map_err(|e| e.message().to_string())?;
+        let checked_out = head.symbolic_target().unwrap_or("HEAD") == preview.refname;
+        if checked_out {
+            let current = repo.find_commit(git2::Oid::from_str(&preview.current).map_err(|e| e.message().to_string())?).map_err(|e| e.message().to_string())?;
+            // Undoing a commit puts its changes back into the index instead of dropping them
+            let keeps_changes = matches!(preview.operation.as_str(), "commit" | "amend" | "squash");
+            if keeps_changes || current.tree_id() == target.tree_id() {
+                repo.reset(target.as_object(), ResetType::Soft, None).map_err(|e| e.message().to_string())?;
+            } else {
+                let mut options = StatusOptions::new();
+                options.include_untracked(false);
+                let dirty = repo.statuses(Some(&mut options)).map(|s| !s.is_empty()).unwrap_or(true);
+                if dirty {
+                    return Err("err-undo-dirty".to_string());
+                }
+                repo.reset(target.as_object(), ResetType::Hard, None).map_err(|e| e.message().to_string())?;
+            }
+        } else {
+            repo.reference(&preview.refname, target.id(), true, &format!("gitamicus: undo {}", preview.operation)).map_err(|e| e.message().to_string())?;
+        }
+
+        let _ = std::fs::remove_file(repo.path().join(LAST_OPERATION_FILE));
+        Ok(())
+    }
+
+    pub fn get_snapshots(path: &str) -> Result<Vec<snapshots::SnapshotInfo>, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        snapshots::list(&repo)
+    }
+
+    pub fn restore_snapshot(path: &str, refname: &str) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        snapshots::restore(&repo, refname)
+    }
+
+    pub fn delete_snapshot(path: &str, refname: &str) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        snapshots::delete(&repo, refname)
+    }
+
+    /// Number of snapshots kept and their maximum age in days (0 keeps them forever).
+    pub fn get_snapshot_retention(path: &str) -> (usize, i64) {
+        Repository::open(path).map(|repo| snapshots::retention(&repo)).unwrap_or((snapshots::DEFAULT_KEEP, snapshots::DEFAULT_EXPIRY_DAYS))
+    }
+
+    pub fn set_snapshot_retention(path: &str, keep: usize, expiry_days: i64) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        snapshots::set_retention(&repo, keep, expiry_days)?;
+        snapshots::prune(&repo)?;
+        Ok(())
+    }
+
     pub fn stash_save(path: &str) -> Result<(), String> {
         let mut repo = Repository::open(path).map_err(|e| e.message().to_string())?;
         let sig = repo.signature().map_err(|e| e.message().to_string())?;
@@ -389,6 +1170,22 @@ impl GitHandler {
         Ok(())
     }
 
+    pub fn get_stashes(path: &str) -> Result<Vec<(usize, String, String, String)>, String> {
+        let mut repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let mut entries = Vec::new();
+        repo.stash_foreach(|index, message, oid| {
+            entries.push((index, message.to_string(), *oid));
+            true
+        }).map_err(|e| e.message().to_string())?;
+        let mut stashes = Vec::new();
+        for (index, message, oid) in entries {
+            let commit = repo.find_commit(oid).map_err(|e| e.message().to_string())?;
+            let base = commit.parent_id(0).map_err(|e| e.message().to_string())?;
+            stashes.push((index, message, oid.to_string(), base.to_string()));
+        }
+        Ok(stashes)
+    }
+
     #[allow(dead_code)]
     pub fn undo_last_commit(path: &str) -> Result<(), String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
@@ -416,8 +1213,59 @@ impl GitHandler {
         Ok(())
     }
 
-    pub fn create_commit(path: &str, message: &str) -> Result<(), String> {
+    pub fn get_commit_template(path: &str) -> Option<String> {
+        Repository::open(path).ok().and_then(|repo| message::template(&repo))
+    }
+
+    pub fn get_recent_messages(path: &str) -> Vec<String> {
+        Repository::open(path).map(|repo| message::recent_messages(&repo)).unwrap_or_default()
+    }
+
+    /// "Name <email>" of the configured identity, as used for Signed-off-by.
+    pub fn get_signoff_identity(path: &str) -> Result<String, String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let sig = repo.signature().map_err(|_| "err-no-git-config".to_string())?;
+        Ok(format!("{} <{}>", sig.name().unwrap_or_default(), sig.email().unwrap_or_default()))
+    }
+
+    /// Authors of the history as "Name <email>", mapped through .mailmap and
+    /// ordered by number of commits.
+    pub fn get_authors(path: &str) -> Result<Vec<String>, String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        let mailmap = repo.mailmap().map_err(|e| e.message().to_string())?;
+        let mut revwalk = repo.revwalk().map_err(|e| e.message().to_string())?;
+        revwalk.push_glob("refs/heads/*").map_err(|e| e.message().to_string())?;
+        let _ = revwalk.push_head();
+
+        let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+        for oid in revwalk.flatten().take(AUTHOR_WALK_LIMIT) {
+            let Ok(commit) = repo.find_commit(oid) else { continue };
+            let Ok(author) = commit.author_with_mailmap(&mailmap) else { continue };
+            let email = author.email().unwrap_or_default().to_string();
+            let entry = counts.entry(email.to_lowercase()).or_insert_with(|| (format!("{} <{}>", author.name().unwrap_or_default(), email), 0));
+            entry.1 += 1;
+        }
+        let mut authors: Vec<(String, usize)> = counts.into_values().collect();
+        authors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        Ok(authors.into_iter().map(|(a, _)| a).collect())
+    }
+
+    /// Default state of the "sign" toggle, taken from `commit.gpgsign`.
+    pub fn is_signing_enabled(path: &str) -> bool {
+        Repository::open(path).map(|repo| signing::signing_enabled(&repo)).unwrap_or(false)
+    }
+
+    /// Commits the index, running the commit hooks unless `verify` is off
+    /// (which, like `git commit --no-verify`, skips pre-commit and commit-msg).
+    /// With `amend` the commit replaces HEAD, keeping its author and parents.
+    pub fn create_commit(path: &str, message: &str, sign: bool, verify: bool, amend: bool) -> Result<(), String> {
+        let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
+        if verify {
+            hooks::run(&repo, "pre-commit", &[], "")?;
+        }
+        let message = hooks::run_message_hooks(&repo, message, verify)?;
+
+        // Read the index only now, pre-commit hooks may have staged files
         let mut index = repo.index().map_err(|e| e.message().to_string())?;
         let tree_id = index.write_tree().map_err(|e| e.message().to_string())?;
         let tree = repo.find_tree(tree_id).map_err(|e| e.message().to_string())?;
@@ -431,25 +1279,514 @@ impl GitHandler {
             Err(e) => return Err(e.message().to_string()),
         };
 
-        let parents: Vec<&git2::Commit> = parent_commit.iter().collect();
+        if amend && parent_commit.is_none() {
+            return Err("err-head-not-found".to_string());
+        }
+        let (author, parent_commits): (Signature, Vec<git2::Commit>) = match parent_commit {
+            Some(head_commit) if amend => (head_commit.author().to_owned(), head_commit.parents().collect()),
+            head_commit => (sig.clone(), head_commit.into_iter().collect()),
+        };
+        let parents: Vec<&git2::Commit> = parent_commits.iter().collect();
 
-        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
-            .map_err(|e| e.message().to_string())?;
+        let before = head_target(&repo);
+        write_commit(&repo, &author, &sig, &message, &tree, &parents, sign)?;
+        record_operation(&repo, if amend { "amend" } else { "commit" }, before);
+        // Like git, a failing post-commit hook does not undo the commit
+        let _ = hooks::run(&repo, "post-commit", &[], "");
         Ok(())
     }
 
-    pub fn get_file_diff(path: &str, file_path: &str) -> Result<String, String> {
+    /// The upstream branch that already contains HEAD, if any. Amending such a
+    /// commit rewrites published history.
+    pub fn get_pushed_upstream(path: &str) -> Option<String> {
+        let repo = Repository::open(path).ok()?;
+        let head = repo.head().ok()?;
+        let head_oid = head.target()?;
+        let (upstream_name, upstream_oid) = upstream_of(&repo, &head)?;
+        let contains = upstream_oid == head_oid || repo.graph_descendant_of(upstream_oid, head_oid).unwrap_or(false);
+        contains.then_some(upstream_name)
+    }
+
+    pub fn get_hook_log() -> Vec<hooks::HookRun> {
+        hooks::log()
+    }
+
+    pub fn clear_hook_log() {
+        hooks::clear_log()
+    }
+
+    pub fn get_file_diff(path: &str, file_path: &str, options: &FileDiffOptions) -> Result<String, String> {
         let repo = Repository::open(path).map_err(|e| e.message().to_string())?;
-        let mut opts = DiffOptions::new();
-        opts.pathspec(file_path);
+        if let Ok(sm) = repo.find_submodule(file_path) {
+            return Ok(submodule_diff(&repo, file_path, sm.index_id(), sm.workdir_id()));
+        }
+        let mut opts = file_diff_options(file_path, options);
         let diff = repo.diff_index_to_workdir(None, Some(&mut opts)).map_err(|e| e.message().to_string())?;
-        let mut diff_text = String::new();
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-            let prefix = match line.origin() { '+' => "+", '-' => "-", ' ' => " ", _ => "" };
-            diff_text.push_str(prefix);
-            diff_text.push_str(std::str::from_utf8(line.content()).unwrap_or(""));
-            true
-        }).map_err(|e| e.message().to_string())?;
-        Ok(diff_text)
+        diff_to_text(&diff)
+    }
+}
+
+fn file_diff_options(file_path: &str, options: &FileDiffOptions) -> DiffOptions {
+    let mut opts = DiffOptions::new();
+    opts.pathspec(file_path)
+        .ignore_whitespace(options.ignore_whitespace)
+        .ignore_whitespace_change(options.ignore_whitespace_change)
+        .ignore_whitespace_eol(options.ignore_eol)
+        .context_lines(options.context_lines);
+    opts
+}
+
+/// Unified diff text with file and `@@` hunk headers, which carry the line numbers.
+fn diff_to_text(diff: &git2::Diff) -> Result<String, String> {
+    let mut diff_text = String::new();
+    diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        let prefix = match line.origin() { '+' => "+", '-' => "-", ' ' => " ", _ => "" };
+        diff_text.push_str(prefix);
+        diff_text.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    }).map_err(|e| e.message().to_string())?;
+    Ok(diff_text)
+}
+
+/// Remembers which ref the operation just moved and where it pointed before the
+/// operation. Some operations move the ref more than once, so the reflog alone does not tell.
+fn record_operation(repo: &Repository, operation: &str, before: git2::Oid) {
+    let Ok(head) = repo.find_reference("HEAD") else { return };
+    let refname = head.symbolic_target().unwrap_or("HEAD").to_string();
+    let Ok(current) = repo.refname_to_id(&refname) else { return };
+    let _ = std::fs::write(repo.path().join(LAST_OPERATION_FILE), format!("{}\n{}\n{}\n{}\n", refname, current, before, operation));
+}
+
+/// The commit HEAD points at, zero on an unborn branch.
+fn head_target(repo: &Repository) -> git2::Oid {
+    repo.head().ok().and_then(|h| h.target()).unwrap_or_else(git2::Oid::zero)
+}
+
+/// Commits reachable from `from` but not from `hide`, newest first.
+fn commits_between(repo: &Repository, from: git2::Oid, hide: git2::Oid) -> Vec<(String, String)> {
+    let Ok(mut revwalk) = repo.revwalk() else { return Vec::new() };
+    if revwalk.push(from).is_err() || revwalk.hide(hide).is_err() {
+        return Vec::new();
+    }
+    revwalk.flatten()
+        .take(PREVIEW_LIMIT)
+        .filter_map(|oid| repo.find_commit(oid).ok().map(|c| (oid.to_string(), c.summary().unwrap_or_default().to_string())))
+        .collect()
+}
+
+/// The .git directory shared by all worktrees. Linked worktrees point to it
+/// through a "commondir" file.
+fn common_dir(repo: &Repository) -> std::path::PathBuf {
+    let git_dir = repo.path();
+    std::fs::read_to_string(git_dir.join("commondir"))
+        .ok()
+        .and_then(|c| git_dir.join(c.trim()).canonicalize().ok())
+        .unwrap_or_else(|| git_dir.to_path_buf())
+}
+
+/// The checkout already happened, so the hook's exit code only shows up in the log.
+fn run_post_checkout(repo: &Repository, old_head: git2::Oid) {
+    let new_head = repo.head().ok().and_then(|h| h.target()).unwrap_or_else(git2::Oid::zero);
+    let _ = hooks::run(repo, "post-checkout", &[&old_head.to_string(), &new_head.to_string(), "1"], "");
+}
+
+/// Commits reachable from `tip` that no other ref keeps alive once `moved_ref`
+/// stops pointing at them. `keep` lists further commits that stay reachable.
+fn unreachable_commits(repo: &Repository, tip: git2::Oid, moved_ref: Option<&str>, keep: &[git2::Oid]) -> Vec<(String, String)> {
+    let Ok(mut revwalk) = repo.revwalk() else { return Vec::new() };
+    if revwalk.push(tip).is_err() {
+        return Vec::new();
+    }
+    for oid in keep.iter() {
+        let _ = revwalk.hide(*oid);
+    }
+    if let Ok(references) = repo.references() {
+        for reference in references.flatten() {
+            let name = reference.name().unwrap_or_default();
+            // Recovery snapshots would hide everything they were taken on
+            if Some(name) == moved_ref || name.starts_with(snapshots::SNAPSHOT_REF_PREFIX) {
+                continue;
+            }
+            if let Ok(commit) = reference.peel_to_commit() {
+                let _ = revwalk.hide(commit.id());
+            }
+        }
+    }
+    revwalk.flatten()
+        .take(PREVIEW_LIMIT)
+        .filter_map(|oid| repo.find_commit(oid).ok().map(|c| (oid.to_string(), c.summary().unwrap_or_default().to_string())))
+        .collect()
+}
+
+/// Where the last fetch saw the branch that `push` updates.
+fn remote_tracking_oid(repo: &Repository, head: &git2::Reference) -> Option<git2::Oid> {
+    upstream_of(repo, head).map(|(_, oid)| oid)
+}
+
+/// The remote and remote ref name a push of `local_ref` goes to: the configured
+/// upstream, otherwise the same name on origin.
+fn push_destination(repo: &Repository, local_ref: &str) -> (String, String) {
+    let remote = repo.branch_upstream_remote(local_ref).ok().and_then(|b| b.as_str().map(str::to_string));
+    let branch = local_ref.strip_prefix("refs/heads/").unwrap_or(local_ref);
+    let merge = repo.config().ok().and_then(|c| c.get_string(&format!("branch.{}.merge", branch)).ok());
+    match (remote, merge) {
+        (Some(remote), Some(merge)) if remote != "." => (remote, merge),
+        _ => ("origin".to_string(), local_ref.to_string()),
+    }
+}
+
+/// What a `fixup!` or `squash!` subject points at, with nested prefixes removed.
+fn autosquash_subject(summary: &str) -> Option<&str> {
+    let mut rest = summary.strip_prefix("fixup! ").or_else(|| summary.strip_prefix("squash! "))?;
+    while let Some(inner) = rest.strip_prefix("fixup! ").or_else(|| rest.strip_prefix("squash! ")) {
+        rest = inner;
+    }
+    Some(rest)
+}
+
+/// Applies the changes of `commit` onto `onto`, like a cherry-pick without a commit.
+fn pick_tree<'r>(repo: &'r Repository, commit: &git2::Commit, onto: &git2::Tree<'r>) -> Result<git2::Tree<'r>, String> {
+    let ancestor = match commit.parent(0) {
+        Ok(parent) => parent.tree().map_err(|e| e.message().to_string())?,
+        Err(_) => empty_tree(repo)?,
+    };
+    let their = commit.tree().map_err(|e| e.message().to_string())?;
+    let mut index = repo.merge_trees(&ancestor, onto, &their, None).map_err(|e| e.message().to_string())?;
+    if index.has_conflicts() {
+        return Err("err-autosquash-conflict".to_string());
+    }
+    let tree_id = index.write_tree_to(repo).map_err(|e| e.message().to_string())?;
+    repo.find_tree(tree_id).map_err(|e| e.message().to_string())
+}
+
+/// The changes a commit made to its first parent, with binary content so they can be applied.
+fn split_diff<'r>(repo: &'r Repository, commit: &git2::Commit) -> Result<git2::Diff<'r>, String> {
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().map_err(|e| e.message().to_string())?),
+        Err(_) => None,
+    };
+    let tree = commit.tree().map_err(|e| e.message().to_string())?;
+    let mut opts = DiffOptions::new();
+    opts.show_binary(true);
+    repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts)).map_err(|e| e.message().to_string())
+}
+
+fn delta_path(delta: &git2::DiffDelta) -> String {
+    delta.new_file().path().or_else(|| delta.old_file().path()).map(|p| p.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Applies only the selected (path, hunk index) pairs of `diff` to `base`. A file
+/// without hunks is applied whole when its index 0 is selected.
+fn apply_hunks<'r>(repo: &'r Repository, base: &git2::Tree<'r>, diff: &git2::Diff, selected: &HashSet<(String, usize)>) -> Result<git2::Tree<'r>, String> {
+    let current = std::cell::RefCell::new((String::new(), 0usize));
+    let mut opts = git2::ApplyOptions::new();
+    opts.delta_callback(|delta| {
+        let Some(delta) = delta else { return false };
+        let path = delta_path(&delta);
+        let wanted = selected.iter().any(|(p, _)| *p == path);
+        *current.borrow_mut() = (path, 0);
+        wanted
+    });
+    opts.hunk_callback(|_| {
+        let mut current = current.borrow_mut();
+        let key = (current.0.clone(), current.1);
+        current.1 += 1;
+        selected.contains(&key)
+    });
+    let mut index = repo.apply_to_tree(base, diff, Some(&mut opts)).map_err(|e| e.message().to_string())?;
+    let tree_id = index.write_tree_to(repo).map_err(|e| e.message().to_string())?;
+    repo.find_tree(tree_id).map_err(|e| e.message().to_string())
+}
+
+fn empty_tree(repo: &Repository) -> Result<git2::Tree<'_>, String> {
+    let tree_id = repo.treebuilder(None).and_then(|b| b.write()).map_err(|e| e.message().to_string())?;
+    repo.find_tree(tree_id).map_err(|e| e.message().to_string())
+}
+
+/// The upstream of the branch HEAD points to, as name and commit.
+fn upstream_of(repo: &Repository, head: &git2::Reference) -> Option<(String, git2::Oid)> {
+    let branch = head.shorthand()?;
+    // Pushes from GitAmicus do not configure an upstream, origin/<branch> is the fallback
+    match repo.find_branch(branch, git2::BranchType::Local).ok().and_then(|b| b.upstream().ok()) {
+        Some(upstream) => Some((upstream.name().ok().flatten().unwrap_or_default().to_string(), upstream.get().target()?)),
+        None => Some((format!("origin/{}", branch), repo.find_reference(&format!("refs/remotes/origin/{}", branch)).ok()?.target()?)),
+    }
+}
+
+/// `sha` and the commits after it up to HEAD, oldest first. Refuses commits that are
+/// not on the current branch, merges in the range and commits already on the upstream.
+fn rewrite_range<'r>(repo: &'r Repository, sha: &str) -> Result<Vec<git2::Commit<'r>>, String> {
+    let head = repo.head().map_err(|_| "err-head-not-found".to_string())?;
+    let head_oid = head.target().ok_or("err-head-not-found".to_string())?;
+    let target = repo.revparse_single(sha).and_then(|o| o.peel_to_commit()).map_err(|e| e.message().to_string())?;
+    let oid = target.id();
+    if oid != head_oid && !repo.graph_descendant_of(head_oid, oid).unwrap_or(false) {
+        return Err("err-rewrite-not-on-branch".to_string());
+    }
+    if let Some((_, upstream_oid)) = upstream_of(repo, &head) {
+        if upstream_oid == oid || repo.graph_descendant_of(upstream_oid, oid).unwrap_or(false) {
+            return Err("err-rewrite-pushed".to_string());
+        }
+    }
+
+    let mut revwalk = repo.revwalk().map_err(|e| e.message().to_string())?;
+    revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE).map_err(|e| e.message().to_string())?;
+    revwalk.push(head_oid).map_err(|e| e.message().to_string())?;
+    for parent in target.parent_ids() {
+        revwalk.hide(parent).map_err(|e| e.message().to_string())?;
+    }
+    let mut commits = Vec::new();
+    for id in revwalk.flatten() {
+        let commit = repo.find_commit(id).map_err(|e| e.message().to_string())?;
+        if commit.parent_count() > 1 {
+            return Err("err-rewrite-merge".to_string());
+        }
+        commits.push(commit);
+    }
+    Ok(commits)
+}
+
+fn update_submodules(repo: &Repository) -> Result<(), String> {
+    for mut sm in repo.submodules().map_err(|e| e.message().to_string())? {
+        sm.update(true, None).map_err(|e| e.message().to_string())?;
+        let sub_repo = sm.open().map_err(|e| e.message().to_string())?;
+        update_submodules(&sub_repo)?;
+    }
+    Ok(())
+}
+
+/// Describes a change of the commit a submodule points to, followed by the
+/// commits added (+) or removed (-) between the two versions.
+fn submodule_diff(repo: &Repository, sub_path: &str, old: Option<git2::Oid>, new: Option<git2::Oid>) -> String {
+    let short = |id: Option<git2::Oid>| id.map(|i| i.to_string()[..7].to_string()).unwrap_or_else(|| "0000000".to_string());
+    if old == new {
+        return format!("Subproject commit {} (modified content)\n", short(new));
+    }
+    let mut text = format!("Subproject commit {}\u{2192}{}\n", short(old), short(new));
+
+    let Some(sub_repo) = repo.find_submodule(sub_path).ok().and_then(|sm| sm.open().ok()) else {
+        return text;
+    };
+    let (Some(old), Some(new)) = (old, new) else {
+        return text;
+    };
+    for (from, to, marker) in [(old, new, '+'), (new, old, '-')] {
+        let Ok(mut revwalk) = sub_repo.revwalk() else { continue };
+        if revwalk.push(to).is_err() || revwalk.hide(from).is_err() {
+            text.push_str(&format!("  ({} not present in the submodule)\n", short(Some(to))));
+            break;
+        }
+        for oid in revwalk.flatten() {
+            if let Ok(commit) = sub_repo.find_commit(oid) {
+                text.push_str(&format!("{} {} {}\n", marker, &oid.to_string()[..7], commit.summary().unwrap_or_default()));
+            }
+        }
+    }
+    text
+}
+
+/// Writes a commit and moves HEAD (or the branch it points to) onto it.
+fn write_commit(repo: &Repository, author: &Signature, committer: &Signature, message: &str, tree: &git2::Tree, parents: &[&git2::Commit], sign: bool) -> Result<git2::Oid, String> {
+    let oid = create_commit_object(repo, author, committer, message, tree, parents, sign)?;
+
+    // HEAD is moved by hand, libgit2 only updates a ref whose tip is the first parent
+    let summary = message.lines().next().unwrap_or_default();
+    let current = repo.head().ok().and_then(|h| h.target());
+    let reflog_msg = if current.is_some() && current != parents.first().map(|p| p.id()) {
+        format!("commit (amend): {}", summary)
+    } else {
+        format!("commit: {}", summary)
+    };
+    move_head(repo, oid, &reflog_msg)?;
+    Ok(oid)
+}
+
+/// Points the branch HEAD is on (or a detached HEAD) at `oid` without touching the
+/// index or working tree.
+fn move_head(repo: &Repository, oid: git2::Oid, reflog_msg: &str) -> Result<(), String> {
+    let head = repo.find_reference("HEAD").map_err(|e| e.message().to_string())?;
+    match head.symbolic_target() {
+        Some(branch) => {
+            repo.reference(branch, oid, true, reflog_msg).map_err(|e| e.message().to_string())?;
+        }
+        None => repo.set_head_detached(oid).map_err(|e| e.message().to_string())?,
+    }
+    Ok(())
+}
+
+/// Recreates a linear run of commits on top of `onto`, keeping their trees, messages
+/// and signatures. Returns the new tip.
+fn replay<'r>(repo: &'r Repository, commits: &[git2::Commit], onto: git2::Commit<'r>, sign: bool) -> Result<git2::Commit<'r>, String> {
+    let mut tip = onto;
+    for commit in commits {
+        let tree = commit.tree().map_err(|e| e.message().to_string())?;
+        let oid = create_commit_object(repo, &commit.author(), &commit.committer(), commit.message().unwrap_or_default(), &tree, &[&tip], sign)?;
+        tip = repo.find_commit(oid).map_err(|e| e.message().to_string())?;
+    }
+    Ok(tip)
+}
+
+/// Writes a commit object without moving any ref. Signed commits go through
+/// `commit_create_buffer` and `commit_signed`.
+fn create_commit_object(repo: &Repository, author: &Signature, committer: &Signature, message: &str, tree: &git2::Tree, parents: &[&git2::Commit], sign: bool) -> Result<git2::Oid, String> {
+    if sign {
+        let buffer = repo.commit_create_buffer(author, committer, message, tree, parents).map_err(|e| e.message().to_string())?;
+        let content = buffer.as_str().ok_or("err-signing-failed".to_string())?;
+        let signature = signing::sign_buffer(repo, content)?;
+        repo.commit_signed(content, &signature, None).map_err(|e| e.message().to_string())
+    } else {
+        repo.commit(None, author, committer, message, tree, parents).map_err(|e| e.message().to_string())
+    }
+}
+
+/// Walks the history from the start points pushed onto `revwalk`, which `start`
+/// describes for the cache of the modes that walk the full window.
+fn walk_history(repo_path: &str, repo: &Repository, mut revwalk: git2::Revwalk, start: &str, limit: usize, options: &HistoryOptions) -> Result<Vec<(String, String, String, String, Vec<String>)>, String> {
+    let full_walk = options.simplify_by_decoration || options.sort_mode == "author-date";
+    // Any ref move can change the result, so the ref state is part of the key
+    let cache_key = full_walk.then(|| format!("{}\n{}\n{}\n{:?}\n{}", repo_path, start, limit, options, ref_state(repo)));
+    if let Some(key) = &cache_key {
+        if let Ok(cache) = HISTORY_CACHE.lock() {
+            if let Some((cached_key, commits)) = cache.as_ref() {
+                if cached_key == key {
+                    return Ok(commits.clone());
+                }
+            }
+        }
+    }
+
+    let sorting = match options.sort_mode.as_str() {
+        "topo" => Sort::TOPOLOGICAL,
+        _ => Sort::TOPOLOGICAL | Sort::TIME,
+    };
+    revwalk.set_sorting(sorting).map_err(|e| e.message().to_string())?;
+    if options.first_parent {
+        revwalk.simplify_first_parent().map_err(|e| e.message().to_string())?;
+    }
+
+    // Simplification needs the history beyond the visible rows to find the next decorated
+    // commit, and the author-date order can put commits from deep in the walk on top
+    let walk_limit = if full_walk { FULL_WALK_LIMIT } else { limit };
+    let mut commits = collect_commits(repo, revwalk, walk_limit)?;
+
+    if options.first_parent {
+        for commit in commits.iter_mut() {
+            commit.4.truncate(1);
+        }
+    }
+    if options.sort_mode == "author-date" {
+        commits = sort_by_author_date(repo, commits);
+    }
+    if options.simplify_by_decoration {
+        let decorated = GitHandler::get_commit_refs(repo_path)?;
+        commits = simplify_by_decoration(commits, |sha| decorated.contains_key(sha));
+    }
+    commits.truncate(limit);
+    if let Some(key) = cache_key {
+        if let Ok(mut cache) = HISTORY_CACHE.lock() {
+            *cache = Some((key, commits.clone()));
+        }
+    }
+    Ok(commits)
+}
+
+/// Every ref name with its target, and where HEAD points.
+fn ref_state(repo: &Repository) -> String {
+    let mut state = repo.head().ok().map(|h| format!("{} {}", h.name().unwrap_or_default(), h.target().unwrap_or_else(git2::Oid::zero))).unwrap_or_default();
+    if let Ok(references) = repo.references() {
+        for reference in references.flatten() {
+            state.push_str(&format!("\n{} {}", reference.name().unwrap_or_default(), reference.target().unwrap_or_else(git2::Oid::zero)));
+        }
+    }
+    state
+}
+
+fn collect_commits(repo: &Repository, revwalk: git2::Revwalk, limit: usize) -> Result<Vec<(String, String, String, String, Vec<String>)>, String> {
+    let mut commits = Vec::new();
+    for oid in revwalk.take(limit) {
+        let oid = oid.map_err(|e| format!("Failed to walk revision: {}", e))?;
+        let commit = repo.find_commit(oid).map_err(|e| format!("Failed to find commit: {}", e))?;
+        let parents: Vec<String> = commit.parents().map(|p| p.id().to_string()).collect();
+        let time = DateTime::from_timestamp(commit.time().seconds(), 0).map(|dt| dt.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string()).unwrap_or_default();
+        commits.push((commit.id().to_string(), commit.summary().unwrap_or("").to_string(), commit.author().name().unwrap_or("").to_string(), time, parents));
+    }
+    Ok(commits)
+}
+
+/// Reorders a topologically sorted list so that, among the commits whose
+/// children have all been shown, the one with the newest author date comes first.
+fn sort_by_author_date(repo: &Repository, commits: Vec<(String, String, String, String, Vec<String>)>) -> Vec<(String, String, String, String, Vec<String>)> {
+    let author_time: HashMap<String, i64> = commits.iter()
+        .map(|c| {
+            let seconds = git2::Oid::from_str(&c.0).ok()
+                .and_then(|oid| repo.find_commit(oid).ok())
+                .map(|commit| commit.author().when().seconds())
+                .unwrap_or(0);
+            (c.0.clone(), seconds)
+        })
+        .collect();
+
+    let mut pending_children: HashMap<String, usize> = HashMap::new();
+    for commit in commits.iter() {
+        for parent in commit.4.iter() {
+            if author_time.contains_key(parent) {
+                *pending_children.entry(parent.clone()).or_default() += 1;
+            }
+        }
+    }
+
+    let time_of = |sha: &String| author_time.get(sha).copied().unwrap_or(0);
+    let mut by_sha: HashMap<String, (String, String, String, String, Vec<String>)> = commits.iter().map(|c| (c.0.clone(), c.clone())).collect();
+    // Newest author date first among the commits whose children are all placed
+    let mut ready: BinaryHeap<(i64, String)> = commits.iter().filter(|c| !pending_children.contains_key(&c.0)).map(|c| (time_of(&c.0), c.0.clone())).collect();
+    let mut sorted = Vec::with_capacity(commits.len());
+
+    while let Some((_, sha)) = ready.pop() {
+        let Some(commit) = by_sha.remove(&sha) else { continue };
+        for parent in commit.4.iter() {
+            if let Some(count) = pending_children.get_mut(parent) {
+                *count -= 1;
+                if *count == 0 {
+                    ready.push((time_of(parent), parent.clone()));
+                }
+            }
+        }
+        sorted.push(commit);
+    }
+    sorted
+}
+
+/// Keeps only decorated commits and rewrites their parents to the nearest
+/// decorated ancestors, collapsing the linear runs in between.
+fn simplify_by_decoration<F: Fn(&str) -> bool>(commits: Vec<(String, String, String, String, Vec<String>)>, is_decorated: F) -> Vec<(String, String, String, String, Vec<String>)> {
+    // Nearest decorated ancestors (inclusive) of every walked commit, filled parents first
+    let mut nearest: HashMap<String, Vec<String>> = HashMap::new();
+    for commit in commits.iter().rev() {
+        let reachable = if is_decorated(&commit.0) {
+            vec![commit.0.clone()]
+        } else {
+            collect_nearest(&commit.4, &nearest)
+        };
+        nearest.insert(commit.0.clone(), reachable);
+    }
+
+    commits.into_iter()
+        .filter(|c| is_decorated(&c.0))
+        .map(|mut c| {
+            c.4 = collect_nearest(&c.4, &nearest);
+            c
+        })
+        .collect()
+}
+
+fn collect_nearest(parents: &[String], nearest: &HashMap<String, Vec<String>>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    for parent in parents {
+        for sha in nearest.get(parent).map(|v| v.as_slice()).unwrap_or(&[]) {
+            if !result.contains(sha) {
+                result.push(sha.clone());
+            }
+        }
     }
+    result
 }
\ No newline at end of file
@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, OnceLock};
+
+use git2::{Oid, Repository};
+
+/// Result of checking a commit signature against the locally trusted keys.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureInfo {
+    pub state: String,  // "unsigned", "good", "untrusted", "unverified" or "bad"
+    pub format: String, // "openpgp", "x509", "ssh" or empty when unsigned
+    pub signer: String, // Key owner or principal as reported by the verifying program
+}
+
+impl SignatureInfo {
+    fn new(state: &str, format: &str, signer: &str) -> Self {
+        SignatureInfo { state: state.to_string(), format: format.to_string(), signer: signer.to_string() }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.state == "good"
+    }
+}
+
+// Verifying spawns external programs, so results are kept per repository and commit,
+// together with the state of the trusted keys they were checked against
+type VerifyCache = HashMap<(String, Oid), (String, SignatureInfo)>;
+static VERIFY_CACHE: OnceLock<Mutex<VerifyCache>> = OnceLock::new();
+
+// Keyring and trust files of gpg and gpgsm, relative to the GnuPG home
+const GNUPG_TRUST_FILES: &[&str] = &["pubring.kbx", "pubring.gpg", "trustdb.gpg", "trustlist.txt"];
+
+/// Whether new commits are signed unless the user says otherwise (`commit.gpgsign`).
+pub fn signing_enabled(repo: &Repository) -> bool {
+    repo.config().and_then(|c| c.get_bool("commit.gpgsign")).unwrap_or(false)
+}
+
+/// Signs a commit buffer the way `git commit -S` does, using `gpg.format`
+/// (openpgp, x509 or ssh), the matching `gpg.*.program` and `user.signingkey`.
+pub fn sign_buffer(repo: &Repository, buffer: &str) -> Result<String, String> {
+    let config = repo.config().map_err(|e| e.message().to_string())?;
+    let format = config.get_string("gpg.format").unwrap_or_else(|_| "openpgp".to_string());
+    let key = config.get_string("user.signingkey").ok().filter(|k| !k.trim().is_empty());
+
+    match format.as_str() {
+        "ssh" => {
+            let program = config.get_string("gpg.ssh.program").unwrap_or_else(|_| "ssh-keygen".to_string());
+            let key = key.ok_or("err-signing-key-missing".to_string())?;
+            sign_ssh(&program, &key, buffer)
+        }
+        "openpgp" | "x509" => {
+            let default_program = if format == "x509" { "gpgsm" } else { "gpg" };
+            let program = config.get_string(&format!("gpg.{}.program", format))
+                .or_else(|_| config.get_string("gpg.program"))
+                .unwrap_or_else(|_| default_program.to_string());
+            // Without an explicit key gpg picks one matching the committer identity
+            let key = match key {
+                Some(k) => k,
+                None => {
+                    let sig = repo.signature().map_err(|_| "err-no-git-config".to_string())?;
+                    format!("{} <{}>", sig.name().unwrap_or_default(), sig.email().unwrap_or_default())
+                }
+            };
+            run_signer(&program, &["--status-fd=2", "-bsau", &key], buffer)
+        }
+        _ => Err("err-signing-format".to_string()),
+    }
+}
+
+fn sign_ssh(program: &str, key: &str, buffer: &str) -> Result<String, String> {
+    let literal = key.strip_prefix("key::").unwrap_or(key);
+    if literal.starts_with("ssh-") || literal.starts_with("ecdsa-") || literal.starts_with("sk-") {
+        // A literal public key: the private half has to come from the ssh-agent
+        let key_file = std::env::temp_dir().join(format!("gitamicus-signingkey-{}.pub", std::process::id()));
+        std::fs::write(&key_file, format!("{}\n", literal)).map_err(|e| e.to_string())?;
+        let result = run_signer(program, &["-Y", "sign", "-n", "git", "-U", "-f", &key_file.to_string_lossy()], buffer);
+        let _ = std::fs::remove_file(&key_file);
+        result
+    } else {
+        let key_file = expand_home(literal);
+        run_signer(program, &["-Y", "sign", "-n", "git", "-f", &key_file.to_string_lossy()], buffer)
+    }
+}
+
+fn run_signer(program: &str, args: &[&str], buffer: &str) -> Result<String, String> {
+    let (success, stdout, stderr) = run_program(program, args, buffer)?;
+    if !success {
+        let reason = stderr.lines().filter(|l| !l.starts_with("[GNUPG:]")).collect::<Vec<_>>().join(" ");
+        return Err(format!("{}: {}", program, reason.trim()));
+    }
+    if stdout.trim().is_empty() {
+        return Err("err-signing-failed".to_string());
+    }
+    Ok(stdout)
+}
+
+/// Checks the signature of a commit, reusing earlier results for the same repository
+/// until the trusted keys change. `trust` comes from `trust_state`.
+pub fn verify_commit(repo: &Repository, oid: Oid, trust: &str) -> SignatureInfo {
+    let key = (repo.path().to_string_lossy().to_string(), oid);
+    let cache = VERIFY_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some((cached_trust, info)) = cache.lock().ok().and_then(|c| c.get(&key).cloned()) {
+        if cached_trust == *trust {
+            return info;
+        }
+    }
+
+    let info = match repo.extract_signature(&oid, None) {
+        Ok((signature, signed_data)) => {
+            let signature = signature.as_str().unwrap_or_default().to_string();
+            let signed_data = signed_data.as_str().unwrap_or_default().to_string();
+            verify_signature(repo, oid, &signature, &signed_data)
+        }
+        Err(_) => SignatureInfo::new("unsigned", "", ""),
+    };
+
+    if let Ok(mut c) = cache.lock() {
+        c.insert(key, (trust.to_string(), info.clone()));
+    }
+    info
+}
+
+/// The allowed signers file and the GnuPG keyrings with their modification times, so
+/// that editing `allowed_signers` or importing a key invalidates cached results.
+pub fn trust_state(repo: &Repository) -> String {
+    let mut files = Vec::new();
+    if let Some(allowed) = repo.config().ok().and_then(|c| c.get_string("gpg.ssh.allowedSignersFile").ok()) {
+        files.push(expand_home(&allowed));
+    }
+    let gnupg_home = std::env::var_os("GNUPGHOME").map(PathBuf::from)
+        .or_else(|| directories::BaseDirs::new().map(|d| d.home_dir().join(".gnupg")));
+    if let Some(home) = gnupg_home {
+        files.extend(GNUPG_TRUST_FILES.iter().map(|f| home.join(f)));
+    }
+    files.iter()
+        .map(|f| {
+            let modified = std::fs::metadata(f).and_then(|m| m.modified()).ok()
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_nanos())
+                .unwrap_or(0);
+            format!("{} {}\n", f.display(), modified)
+        })
+        .collect()
+}
+
+fn verify_signature(repo: &Repository, oid: Oid, signature: &str, signed_data: &str) -> SignatureInfo {
+    let format = if signature.starts_with("-----BEGIN SSH SIGNATURE") {
+        "ssh"
+    } else if signature.starts_with("-----BEGIN SIGNED MESSAGE") {
+        "x509"
+    } else {
+        "openpgp"
+    };
+
+    let sig_file = std::env::temp_dir().join(format!("gitamicus-sig-{}-{}", std::process::id(), oid));
+    if std::fs::write(&sig_file, signature).is_err() {
+        return SignatureInfo::new("unverified", format, "");
+    }
+    let sig_path = sig_file.to_string_lossy().to_string();
+    let config = repo.config().ok();
+    let get = |name: &str| config.as_ref().and_then(|c| c.get_string(name).ok());
+
+    let info = if format == "ssh" {
+        let program = get("gpg.ssh.program").unwrap_or_else(|| "ssh-keygen".to_string());
+        verify_ssh(&program, get("gpg.ssh.allowedSignersFile").map(|f| expand_home(&f)), &sig_path, signed_data)
+    } else {
+        let default_program = if format == "x509" { "gpgsm" } else { "gpg" };
+        let program = get(&format!("gpg.{}.program", format))
+            .or_else(|| get("gpg.program"))
+            .unwrap_or_else(|| default_program.to_string());
+        verify_gpg(&program, format, &sig_path, signed_data)
+    };
+
+    let _ = std::fs::remove_file(&sig_file);
+    info
+}
+
+fn verify_gpg(program: &str, format: &str, sig_path: &str, signed_data: &str) -> SignatureInfo {
+    let Ok((_, stdout, _)) = run_program(program, &["--status-fd=1", "--verify", sig_path, "-"], signed_data) else {
+        return SignatureInfo::new("unverified", format, "");
+    };
+
+    let mut state = "unverified";
+    let mut signer = String::new();
+    for line in stdout.lines().filter_map(|l| l.strip_prefix("[GNUPG:] ")) {
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        match keyword {
+            "GOODSIG" => {
+                state = "good";
+                signer = rest.split_once(' ').map(|(_, uid)| uid.to_string()).unwrap_or_default();
+            }
+            "EXPSIG" | "EXPKEYSIG" | "REVKEYSIG" => {
+                state = "untrusted";
+                signer = rest.split_once(' ').map(|(_, uid)| uid.to_string()).unwrap_or_default();
+            }
+            "BADSIG" => {
+                state = "bad";
+                signer = rest.split_once(' ').map(|(_, uid)| uid.to_string()).unwrap_or_default();
+            }
+            "TRUST_UNDEFINED" | "TRUST_NEVER" if state == "good" => state = "untrusted",
+            _ => {}
+        }
+    }
+    SignatureInfo::new(state, format, &signer)
+}
+
+fn verify_ssh(program: &str, allowed_signers: Option<PathBuf>, sig_path: &str, signed_data: &str) -> SignatureInfo {
+    if let Some(allowed) = allowed_signers.filter(|f| f.exists()) {
+        let allowed = allowed.to_string_lossy().to_string();
+        let principals = run_program(program, &["-Y", "find-principals", "-f", &allowed, "-s", sig_path], "")
+            .ok()
+            .filter(|(success, _, _)| *success)
+            .map(|(_, stdout, _)| stdout.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        for principal in principals.iter() {
+            if let Ok((true, _, _)) = run_program(program, &["-Y", "verify", "-f", &allowed, "-I", principal, "-n", "git", "-s", sig_path], signed_data) {
+                return SignatureInfo::new("good", "ssh", principal);
+            }
+        }
+        if !principals.is_empty() {
+            return SignatureInfo::new("bad", "ssh", &principals.join(", "));
+        }
+    }
+
+    // The key is not in allowed_signers: the signature can still be checked, but not trusted
+    match run_program(program, &["-Y", "check-novalidate", "-n", "git", "-s", sig_path], signed_data) {
+        Ok((true, stdout, stderr)) => {
+            let output = format!("{}{}", stdout, stderr);
+            let fingerprint = output.split_whitespace().find(|w| w.starts_with("SHA256:")).unwrap_or_default();
+            SignatureInfo::new("untrusted", "ssh", fingerprint)
+        }
+        Ok((false, _, _)) => SignatureInfo::new("bad", "ssh", ""),
+        Err(_) => SignatureInfo::new("unverified", "ssh", ""),
+    }
+}
+
+fn run_program(program: &str, args: &[&str], input: &str) -> Result<(bool, String, String), String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{}: {}", program, e))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes()).map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    Ok((
+        output.status.success(),
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+    ))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => directories::BaseDirs::new()
+            .map(|d| d.home_dir().join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
@@ -0,0 +1,142 @@
+use chrono::{DateTime, Local};
+use git2::{IndexAddOption, Oid, Repository, StatusOptions};
+
+pub const SNAPSHOT_REF_PREFIX: &str = "refs/gitamicus/snapshots/";
+pub const DEFAULT_KEEP: usize = 20;
+pub const DEFAULT_EXPIRY_DAYS: i64 = 30;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SnapshotInfo {
+    pub refname: String,
+    pub sha: String,
+    pub message: String,
+    pub time: String,
+    pub files: Vec<String>, // Paths that differ from the commit the snapshot was taken on
+}
+
+/// Retention limits, read from `gitamicus.snapshotKeep` and `gitamicus.snapshotExpiryDays`.
+pub fn retention(repo: &Repository) -> (usize, i64) {
+    let config = repo.config().ok();
+    let keep = config.as_ref().and_then(|c| c.get_i64("gitamicus.snapshotKeep").ok()).map(|k| k.max(1) as usize).unwrap_or(DEFAULT_KEEP);
+    let days = config.as_ref().and_then(|c| c.get_i64("gitamicus.snapshotExpiryDays").ok()).unwrap_or(DEFAULT_EXPIRY_DAYS);
+    (keep, days)
+}
+
+pub fn set_retention(repo: &Repository, keep: usize, expiry_days: i64) -> Result<(), String> {
+    let mut config = repo.config().map_err(|e| e.message().to_string())?;
+    config.set_i64("gitamicus.snapshotKeep", keep.max(1) as i64).map_err(|e| e.message().to_string())?;
+    config.set_i64("gitamicus.snapshotExpiryDays", expiry_days).map_err(|e| e.message().to_string())?;
+    Ok(())
+}
+
+/// Records the index and working tree (including untracked files) as a stash-like
+/// commit under `refs/gitamicus/snapshots/`. Nothing is written for a clean tree.
+pub fn create_snapshot(repo: &Repository, operation: &str) -> Result<Option<Oid>, String> {
+    let mut status_opts = StatusOptions::new();
+    status_opts.include_untracked(true).recurse_untracked_dirs(true);
+    let is_clean = repo.statuses(Some(&mut status_opts)).map(|s| s.is_empty()).unwrap_or(true);
+    if is_clean || repo.is_bare() {
+        return Ok(None);
+    }
+
+    let sig = repo.signature().or_else(|_| git2::Signature::now("GitAmicus", "gitamicus@localhost")).map_err(|e| e.message().to_string())?;
+    let head = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let head_parents: Vec<&git2::Commit> = head.iter().collect();
+    let branch = repo.head().ok().and_then(|h| h.shorthand().map(|s| s.to_string())).unwrap_or_else(|| "HEAD".to_string());
+    let summary = head.as_ref().and_then(|c| c.summary().map(|s| s.to_string())).unwrap_or_default();
+
+    // The staged state, as git stash records it
+    let mut index = repo.index().map_err(|e| e.message().to_string())?;
+    let index_tree = repo.find_tree(index.write_tree().map_err(|e| e.message().to_string())?).map_err(|e| e.message().to_string())?;
+    let index_oid = repo.commit(None, &sig, &sig, &format!("index on {}: {}", branch, summary), &index_tree, &head_parents)
+        .map_err(|e| e.message().to_string())?;
+    let index_commit = repo.find_commit(index_oid).map_err(|e| e.message().to_string())?;
+
+    // The working tree goes through a separate repository handle so the in-memory
+    // index of `repo` is left untouched and nothing is written to .git/index
+    let scratch = Repository::open(repo.path()).map_err(|e| e.message().to_string())?;
+    let mut work_index = scratch.index().map_err(|e| e.message().to_string())?;
+    work_index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None).map_err(|e| e.message().to_string())?;
+    work_index.update_all(["*"].iter(), None).map_err(|e| e.message().to_string())?;
+    let work_tree = repo.find_tree(work_index.write_tree().map_err(|e| e.message().to_string())?).map_err(|e| e.message().to_string())?;
+
+    // The index commit is always the last parent; on an unborn branch it is the only one
+    let mut parents = head_parents.clone();
+    parents.push(&index_commit);
+    let message = format!("snapshot before {} on {}: {}", operation, branch, summary);
+    let oid = repo.commit(None, &sig, &sig, &message, &work_tree, &parents).map_err(|e| e.message().to_string())?;
+
+    let refname = format!("{}{}", SNAPSHOT_REF_PREFIX, Local::now().format("%Y%m%d-%H%M%S%3f"));
+    repo.reference(&refname, oid, false, &message).map_err(|e| e.message().to_string())?;
+    prune(repo)?;
+    Ok(Some(oid))
+}
+
+pub fn list(repo: &Repository) -> Result<Vec<SnapshotInfo>, String> {
+    let refs = repo.references_glob(&format!("{}*", SNAPSHOT_REF_PREFIX)).map_err(|e| e.message().to_string())?;
+    let mut snapshots = Vec::new();
+    for reference in refs.flatten() {
+        let Some(refname) = reference.name().map(|n| n.to_string()) else { continue };
+        let Ok(commit) = reference.peel_to_commit() else { continue };
+        let base_tree = if commit.parent_count() > 1 { commit.parent(0).ok().and_then(|p| p.tree().ok()) } else { None };
+        let files = commit.tree().ok()
+            .and_then(|tree| repo.diff_tree_to_tree(base_tree.as_ref(), Some(&tree), None).ok())
+            .map(|diff| diff.deltas().filter_map(|d| d.new_file().path().or(d.old_file().path()).map(|p| p.display().to_string())).collect())
+            .unwrap_or_default();
+        snapshots.push(SnapshotInfo {
+            refname,
+            sha: commit.id().to_string(),
+            message: commit.message().unwrap_or_default().to_string(),
+            time: DateTime::from_timestamp(commit.time().seconds(), 0).map(|dt| dt.with_timezone(&Local).format("%Y-%m-%d %H:%M:%S").to_string()).unwrap_or_default(),
+            files,
+        });
+    }
+    // Ref names are timestamps, so this puts the newest snapshot first
+    snapshots.sort_by(|a, b| b.refname.cmp(&a.refname));
+    Ok(snapshots)
+}
+
+/// Brings the working tree and index back to the state of a snapshot without moving HEAD.
+/// The current state is snapshotted first, so restoring can itself be undone.
+pub fn restore(repo: &Repository, refname: &str) -> Result<(), String> {
+    let commit = repo.find_reference(refname).and_then(|r| r.peel_to_commit()).map_err(|e| e.message().to_string())?;
+    let index_commit = commit.parents().next_back().ok_or("err-invalid-ref-name".to_string())?;
+    let index_tree = index_commit.tree().map_err(|e| e.message().to_string())?;
+    let work_tree = commit.tree().map_err(|e| e.message().to_string())?;
+
+    create_snapshot(repo, "restore")?;
+
+    let mut checkout_opts = git2::build::CheckoutBuilder::new();
+    checkout_opts.force();
+    repo.checkout_tree(work_tree.as_object(), Some(&mut checkout_opts)).map_err(|e| e.message().to_string())?;
+
+    let mut index = repo.index().map_err(|e| e.message().to_string())?;
+    index.read_tree(&index_tree).map_err(|e| e.message().to_string())?;
+    index.write().map_err(|e| e.message().to_string())?;
+    Ok(())
+}
+
+pub fn delete(repo: &Repository, refname: &str) -> Result<(), String> {
+    if !refname.starts_with(SNAPSHOT_REF_PREFIX) {
+        return Err("err-invalid-ref-name".to_string());
+    }
+    let mut reference = repo.find_reference(refname).map_err(|e| e.message().to_string())?;
+    reference.delete().map_err(|e| e.message().to_string())
+}
+
+/// Drops snapshots beyond the configured count or older than the configured age.
+pub fn prune(repo: &Repository) -> Result<usize, String> {
+    let (keep, expiry_days) = retention(repo);
+    let cutoff = Local::now().timestamp() - expiry_days * 24 * 60 * 60;
+    let mut pruned = 0;
+    for (i, snapshot) in list(repo)?.iter().enumerate() {
+        let expired = expiry_days > 0 && repo.find_commit(Oid::from_str(&snapshot.sha).map_err(|e| e.message().to_string())?)
+            .map(|c| c.time().seconds() < cutoff)
+            .unwrap_or(false);
+        if i >= keep || expired {
+            delete(repo, &snapshot.refname)?;
+            pruned += 1;
+        }
+    }
+    Ok(pruned)
+}
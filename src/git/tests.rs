@@ -0,0 +1,491 @@
+use super::*;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// An empty directory under the system temp dir, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let n = NEXT_DIR.fetch_add(1, Ordering::SeqCst);
+        let dir = std::env::temp_dir().join(format!("gitamicus-test-{}-{}-{}", std::process::id(), n, name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+
+    fn path(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+fn init_repo(dir: &Path) -> Repository {
+    let repo = Repository::init(dir).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Test").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+    config.set_bool("commit.gpgsign", false).unwrap();
+    repo
+}
+
+/// Writes the files, stages them and commits on top of HEAD.
+fn commit_files(repo: &Repository, files: &[(&str, &str)], message: &str) -> git2::Oid {
+    let workdir = repo.workdir().unwrap();
+    let mut index = repo.index().unwrap();
+    for (name, content) in files {
+        std::fs::write(workdir.join(name), content).unwrap();
+        index.add_path(Path::new(name)).unwrap();
+    }
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let sig = repo.signature().unwrap();
+    let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents).unwrap()
+}
+
+fn head_summaries(repo: &Repository) -> Vec<String> {
+    let mut revwalk = repo.revwalk().unwrap();
+    revwalk.push_head().unwrap();
+    revwalk.map(|oid| repo.find_commit(oid.unwrap()).unwrap().summary().unwrap_or_default().to_string()).collect()
+}
+
+fn read(repo: &Repository, name: &str) -> String {
+    std::fs::read_to_string(repo.workdir().unwrap().join(name)).unwrap()
+}
+
+#[test]
+fn undo_squash_restores_the_squashed_commit() {
+    let dir = TempDir::new("undo-squash");
+    let repo = init_repo(&dir.0);
+    commit_files(&repo, &[("a.txt", "one\n")], "first");
+    commit_files(&repo, &[("a.txt", "two\n")], "second");
+    let before = commit_files(&repo, &[("a.txt", "three\n")], "third");
+
+    GitHandler::squash_parent(dir.path()).unwrap();
+    assert_eq!(head_summaries(&repo), ["third", "first"]);
+
+    let preview = GitHandler::get_undo_preview(dir.path()).unwrap();
+    assert_eq!(preview.target, before.to_string());
+    GitHandler::undo_last_operation(dir.path()).unwrap();
+    assert_eq!(repo.head().unwrap().target(), Some(before));
+    assert_eq!(read(&repo, "a.txt"), "three\n");
+}
+
+#[test]
+fn undo_commit_keeps_the_changes_staged() {
+    let dir = TempDir::new("undo-commit");
+    let repo = init_repo(&dir.0);
+    let first = commit_files(&repo, &[("a.txt", "one\n")], "first");
+    std::fs::write(dir.0.join("a.txt"), "two\n").unwrap();
+    GitHandler::stage_files(dir.path(), vec!["a.txt".to_string()]).unwrap();
+    GitHandler::create_commit(dir.path(), "second", false, false, false).unwrap();
+
+    GitHandler::undo_last_operation(dir.path()).unwrap();
+    assert_eq!(repo.head().unwrap().target(), Some(first));
+    assert_eq!(read(&repo, "a.txt"), "two\n");
+    let statuses = repo.statuses(None).unwrap();
+    assert_eq!(statuses.get(0).unwrap().status(), git2::Status::INDEX_MODIFIED);
+}
+
+/// A bare repository with one commit and a clone of it that tracks its branch.
+fn bare_remote(dir: &TempDir) -> (String, Repository) {
+    let bare = dir.0.join("remote.git");
+    Repository::init_bare(&bare).unwrap();
+    let first = init_repo(&dir.0.join("first"));
+    first.remote("origin", bare.to_str().unwrap()).unwrap();
+    commit_files(&first, &[("a.txt", "one\n")], "first");
+    GitHandler::push(first.workdir().unwrap().to_str().unwrap(), "", "", "normal", false).unwrap();
+    (bare.to_str().unwrap().to_string(), first)
+}
+
+fn clone(url: &str, dir: &Path) -> Repository {
+    let repo = Repository::clone(url, dir).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Other").unwrap();
+    config.set_str("user.email", "other@example.com").unwrap();
+    repo
+}
+
+#[test]
+fn lease_push_is_refused_after_another_push() {
+    let dir = TempDir::new("push-lease");
+    let (url, first) = bare_remote(&dir);
+    let first_path = first.workdir().unwrap().to_str().unwrap().to_string();
+    let second = clone(&url, &dir.0.join("second"));
+    commit_files(&second, &[("b.txt", "from second\n")], "second");
+    GitHandler::push(second.workdir().unwrap().to_str().unwrap(), "", "", "normal", false).unwrap();
+
+    // The first clone has not seen that push, so its lease is stale
+    GitHandler::create_commit(&first_path, "first, reworded", false, false, true).unwrap();
+    assert_eq!(GitHandler::push(&first_path, "", "", "lease", false), Err("err-push-lease".to_string()));
+    let remote = Repository::open_bare(&url).unwrap();
+    let branch = first.head().unwrap().name().unwrap().to_string();
+    assert_eq!(remote.refname_to_id(&branch).unwrap(), second.head().unwrap().target().unwrap());
+
+    GitHandler::fetch(&first_path, "", "").unwrap();
+    GitHandler::push(&first_path, "", "", "lease", false).unwrap();
+    assert_eq!(remote.refname_to_id(&branch).unwrap(), first.head().unwrap().target().unwrap());
+}
+
+#[test]
+fn push_goes_to_the_configured_upstream() {
+    let dir = TempDir::new("push-upstream");
+    let (url, first) = bare_remote(&dir);
+    let first_path = first.workdir().unwrap().to_str().unwrap().to_string();
+    let remote = Repository::open_bare(&url).unwrap();
+    let base = remote.head().unwrap().peel_to_commit().unwrap();
+    remote.branch("review", &base, false).unwrap();
+    GitHandler::fetch(&first_path, "", "").unwrap();
+    let mut branch = first.find_branch(first.head().unwrap().shorthand().unwrap(), git2::BranchType::Local).unwrap();
+    branch.set_upstream(Some("origin/review")).unwrap();
+
+    let pushed = commit_files(&first, &[("a.txt", "two\n")], "second");
+    GitHandler::push(&first_path, "", "", "lease", false).unwrap();
+    assert_eq!(remote.refname_to_id("refs/heads/review").unwrap(), pushed);
+    assert_eq!(remote.head().unwrap().target(), Some(base.id()));
+}
+
+#[test]
+fn failed_worktree_add_removes_its_new_branch() {
+    let dir = TempDir::new("worktree");
+    let repo = init_repo(&dir.0.join("main"));
+    let head = commit_files(&repo, &[("a.txt", "one\n")], "first");
+    let main_path = dir.0.join("main").to_str().unwrap().to_string();
+
+    let taken = dir.0.join("taken");
+    std::fs::create_dir_all(&taken).unwrap();
+    std::fs::write(taken.join("file"), "in the way\n").unwrap();
+    assert!(GitHandler::create_worktree(&main_path, &head.to_string(), taken.to_str().unwrap()).is_err());
+    assert!(repo.find_branch("taken", git2::BranchType::Local).is_err());
+
+    let target = dir.0.join("feature");
+    GitHandler::create_worktree(&main_path, &head.to_string(), target.to_str().unwrap()).unwrap();
+    assert!(repo.find_branch("feature", git2::BranchType::Local).is_ok());
+    assert_eq!(read(&Repository::open(&target).unwrap(), "a.txt"), "one\n");
+}
+
+#[test]
+fn snapshot_before_the_first_commit_can_be_restored() {
+    let dir = TempDir::new("snapshot-unborn");
+    let repo = init_repo(&dir.0);
+    std::fs::write(dir.0.join("staged.txt"), "staged\n").unwrap();
+    std::fs::write(dir.0.join("untracked.txt"), "untracked\n").unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("staged.txt")).unwrap();
+    index.write().unwrap();
+    snapshots::create_snapshot(&repo, "test").unwrap().unwrap();
+
+    std::fs::remove_file(dir.0.join("staged.txt")).unwrap();
+    std::fs::remove_file(dir.0.join("untracked.txt")).unwrap();
+    index.clear().unwrap();
+    index.write().unwrap();
+
+    let snapshot = GitHandler::get_snapshots(dir.path()).unwrap().remove(0);
+    assert_eq!(snapshot.files.len(), 2);
+    GitHandler::restore_snapshot(dir.path(), &snapshot.refname).unwrap();
+    assert_eq!(read(&repo, "untracked.txt"), "untracked\n");
+    let index = Repository::open(&dir.0).unwrap().index().unwrap();
+    assert!(index.get_path(Path::new("staged.txt"), 0).is_some());
+    assert!(index.get_path(Path::new("untracked.txt"), 0).is_none());
+}
+
+#[test]
+fn rewrite_keeps_the_trees_of_later_commits() {
+    let dir = TempDir::new("rewrite");
+    let repo = init_repo(&dir.0);
+    commit_files(&repo, &[("a.txt", "one\n")], "first");
+    let target = commit_files(&repo, &[("a.txt", "two\n")], "second");
+    let head = commit_files(&repo, &[("b.txt", "three\n")], "third");
+
+    assert_eq!(GitHandler::get_rewrite_preview(dir.path(), &target.to_string()), Ok(2));
+    assert_eq!(GitHandler::rewrite_commit(dir.path(), &target.to_string(), Some("second, reworded"), None), Ok(2));
+    assert_eq!(head_summaries(&repo), ["third", "second, reworded", "first"]);
+    let new_head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_ne!(new_head.id(), head);
+    assert_eq!(new_head.tree_id(), repo.find_commit(head).unwrap().tree_id());
+}
+
+#[test]
+fn rewrite_refuses_pushed_commits_and_merges() {
+    let dir = TempDir::new("rewrite-refused");
+    let repo = init_repo(&dir.0);
+    let first = commit_files(&repo, &[("a.txt", "one\n")], "first");
+    let pushed = commit_files(&repo, &[("a.txt", "two\n")], "second");
+    let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+    repo.reference(&format!("refs/remotes/origin/{}", branch), pushed, true, "fetch").unwrap();
+    assert_eq!(GitHandler::get_rewrite_preview(dir.path(), &first.to_string()), Err("err-rewrite-pushed".to_string()));
+    assert_eq!(GitHandler::get_rewrite_preview(dir.path(), &pushed.to_string()), Err("err-rewrite-pushed".to_string()));
+
+    let local = commit_files(&repo, &[("b.txt", "local\n")], "local");
+    let side = {
+        let sig = repo.signature().unwrap();
+        let parent = repo.find_commit(pushed).unwrap();
+        repo.commit(None, &sig, &sig, "side", &parent.tree().unwrap(), &[&parent]).unwrap()
+    };
+    let sig = repo.signature().unwrap();
+    let (head, side) = (repo.find_commit(local).unwrap(), repo.find_commit(side).unwrap());
+    repo.commit(Some("HEAD"), &sig, &sig, "merge", &head.tree().unwrap(), &[&head, &side]).unwrap();
+    assert_eq!(GitHandler::rewrite_commit(dir.path(), &local.to_string(), Some("reworded"), None), Err("err-rewrite-merge".to_string()));
+    assert_eq!(repo.head().unwrap().peel_to_commit().unwrap().summary(), Some("merge"));
+}
+
+#[test]
+fn autosquash_folds_fixups_into_older_commits() {
+    let dir = TempDir::new("autosquash");
+    let repo = init_repo(&dir.0);
+    commit_files(&repo, &[("a.txt", "one\n")], "first");
+    commit_files(&repo, &[("b.txt", "b\n")], "add b");
+    commit_files(&repo, &[("c.txt", "c\n")], "add c");
+    commit_files(&repo, &[("b.txt", "b fixed\n")], "fixup! add b");
+    commit_files(&repo, &[("c.txt", "c fixed\n")], "squash! add c\n\nMention the fix.");
+    let head_tree = repo.head().unwrap().peel_to_tree().unwrap().id();
+
+    assert_eq!(GitHandler::autosquash(dir.path()), Ok(2));
+    assert_eq!(head_summaries(&repo), ["add c", "add b", "first"]);
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.tree_id(), head_tree);
+    assert_eq!(head.message(), Some("add c\n\nMention the fix."));
+    let add_b = head.parent(0).unwrap();
+    let b = add_b.tree().unwrap().get_name("b.txt").unwrap().id();
+    assert_eq!(repo.find_blob(b).unwrap().content(), b"b fixed\n");
+    assert!(add_b.tree().unwrap().get_name("c.txt").is_none());
+}
+
+#[test]
+fn autosquash_leaves_pushed_commits_alone() {
+    let dir = TempDir::new("autosquash-pushed");
+    let repo = init_repo(&dir.0);
+    commit_files(&repo, &[("a.txt", "one\n")], "first");
+    let pushed = commit_files(&repo, &[("a.txt", "two\n")], "second");
+    let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+    repo.reference(&format!("refs/remotes/origin/{}", branch), pushed, true, "fetch").unwrap();
+    let head = commit_files(&repo, &[("a.txt", "three\n")], "fixup! second");
+
+    assert_eq!(GitHandler::autosquash(dir.path()), Err("err-autosquash-nothing".to_string()));
+    assert_eq!(repo.head().unwrap().target(), Some(head));
+}
+
+#[test]
+fn autosquash_refuses_to_fold_across_a_merge() {
+    let dir = TempDir::new("autosquash-merge");
+    let repo = init_repo(&dir.0);
+    commit_files(&repo, &[("a.txt", "one\n")], "first");
+    let target = commit_files(&repo, &[("b.txt", "b\n")], "add b");
+    let sig = repo.signature().unwrap();
+    let target = repo.find_commit(target).unwrap();
+    let side = repo.commit(None, &sig, &sig, "side", &target.tree().unwrap(), &[&target]).unwrap();
+    let side = repo.find_commit(side).unwrap();
+    repo.commit(Some("HEAD"), &sig, &sig, "merge", &target.tree().unwrap(), &[&target, &side]).unwrap();
+    let head = commit_files(&repo, &[("b.txt", "b fixed\n")], "fixup! add b");
+
+    assert_eq!(GitHandler::autosquash(dir.path()), Err("err-rewrite-merge".to_string()));
+    assert_eq!(repo.head().unwrap().target(), Some(head));
+}
+
+#[test]
+fn split_by_hunk_keeps_the_final_tree() {
+    let dir = TempDir::new("split");
+    let repo = init_repo(&dir.0);
+    let lines: Vec<String> = (1..=20).map(|n| format!("line {}\n", n)).collect();
+    commit_files(&repo, &[("a.txt", &lines.concat()), ("b.txt", "b\n")], "first");
+    let mut changed = lines.clone();
+    changed[1] = "line 2 changed\n".to_string();
+    changed[17] = "line 18 changed\n".to_string();
+    let target = commit_files(&repo, &[("a.txt", &changed.concat()), ("b.txt", "b changed\n")], "change both");
+    let after = commit_files(&repo, &[("c.txt", "c\n")], "later");
+    let target_tree = repo.find_commit(target).unwrap().tree_id();
+
+    let files = GitHandler::get_split_files(dir.path(), &target.to_string()).unwrap();
+    let a = files.iter().find(|f| f.path == "a.txt").unwrap();
+    assert_eq!(a.hunks.len(), 2);
+    assert!(a.hunks[1].contains("+line 18 changed"));
+
+    let parts = vec![
+        ("second hunk".to_string(), vec![("a.txt".to_string(), 1)]),
+        ("b".to_string(), vec![("b.txt".to_string(), 0)]),
+    ];
+    assert_eq!(GitHandler::split_commit(dir.path(), &target.to_string(), &parts, "first hunk"), Ok(3));
+    assert_eq!(head_summaries(&repo), ["later", "first hunk", "b", "second hunk", "first"]);
+
+    let head = repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(head.tree_id(), repo.find_commit(after).unwrap().tree_id());
+    let rest = head.parent(0).unwrap();
+    assert_eq!(rest.tree_id(), target_tree);
+    let second_hunk = rest.parent(0).unwrap().parent(0).unwrap();
+    let blob = repo.find_blob(second_hunk.tree().unwrap().get_name("a.txt").unwrap().id()).unwrap();
+    let text = String::from_utf8_lossy(blob.content()).to_string();
+    assert!(text.contains("line 18 changed") && !text.contains("line 2 changed"));
+}
+
+#[test]
+fn author_date_order_covers_the_whole_walk() {
+    let dir = TempDir::new("author-date");
+    let repo = init_repo(&dir.0);
+    let sig = repo.signature().unwrap();
+    commit_files(&repo, &[("a.txt", "one\n")], "first");
+    let base = repo.head().unwrap().peel_to_commit().unwrap();
+    // A side branch authored later than every commit on the long main line
+    let late = Signature::new("Late", "late@example.com", &git2::Time::new(base.time().seconds() + 1_000_000, 0)).unwrap();
+    let side = repo.commit(None, &late, &sig, "late side", &base.tree().unwrap(), &[&base]).unwrap();
+    for n in 0..5 {
+        commit_files(&repo, &[("a.txt", &format!("{}\n", n))], &format!("main {}", n));
+    }
+    repo.branch("side", &repo.find_commit(side).unwrap(), false).unwrap();
+
+    let options = HistoryOptions { sort_mode: "author-date".to_string(), ..Default::default() };
+    let commits = GitHandler::get_latest_commits_full(dir.path(), 2, true, &options).unwrap();
+    assert_eq!(commits[0].1, "late side");
+
+    // A moved ref is not served from the cache
+    commit_files(&repo, &[("a.txt", "newest\n")], "main newest");
+    let commits = GitHandler::get_latest_commits_full(dir.path(), 2, true, &options).unwrap();
+    assert_eq!(commits.iter().map(|c| c.1.as_str()).collect::<Vec<_>>(), ["late side", "main newest"]);
+}
+
+#[test]
+fn commits_on_branches_are_found_with_one_walk() {
+    let dir = TempDir::new("protected");
+    let repo = init_repo(&dir.0);
+    let first = commit_files(&repo, &[("a.txt", "one\n")], "first");
+    let second = commit_files(&repo, &[("a.txt", "two\n")], "second");
+    let main = repo.head().unwrap().shorthand().unwrap().to_string();
+    repo.branch("feature", &repo.find_commit(second).unwrap(), false).unwrap();
+    repo.set_head("refs/heads/feature").unwrap();
+    let feature = commit_files(&repo, &[("b.txt", "b\n")], "feature work");
+
+    let shas = [first, second, feature].map(|o| o.to_string());
+    let on_main = GitHandler::get_commits_on_branches(dir.path(), &[main], &shas).unwrap();
+    assert_eq!(on_main, HashSet::from([first.to_string(), second.to_string()]));
+    assert!(GitHandler::get_commits_on_branches(dir.path(), &["missing".to_string()], &shas).unwrap().is_empty());
+}
+
+#[cfg(unix)]
+fn write_hook(dir: &Path, name: &str, script: &str) {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::create_dir_all(dir).unwrap();
+    let hook = dir.join(name);
+    std::fs::write(&hook, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn failing_pre_commit_hook_blocks_the_commit_unless_skipped() {
+    let dir = TempDir::new("hook-pre-commit");
+    let repo = init_repo(&dir.0);
+    let first = commit_files(&repo, &[("a.txt", "one\n")], "first");
+    write_hook(&repo.path().join("hooks"), "pre-commit", "echo rejected\nexit 1");
+    std::fs::write(dir.0.join("a.txt"), "two\n").unwrap();
+    GitHandler::stage_files(dir.path(), vec!["a.txt".to_string()]).unwrap();
+
+    assert_eq!(GitHandler::create_commit(dir.path(), "second", false, true, false), Err("err-hook-failed".to_string()));
+    assert_eq!(repo.head().unwrap().target(), Some(first));
+
+    GitHandler::create_commit(dir.path(), "second", false, false, false).unwrap();
+    assert_eq!(head_summaries(&repo), ["second", "first"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn commit_msg_hook_can_rewrite_the_message() {
+    let dir = TempDir::new("hook-commit-msg");
+    let repo = init_repo(&dir.0);
+    commit_files(&repo, &[("a.txt", "one\n")], "first");
+    write_hook(&repo.path().join("hooks"), "commit-msg", "echo rewritten > \"$1\"");
+    std::fs::write(dir.0.join("a.txt"), "two\n").unwrap();
+    GitHandler::stage_files(dir.path(), vec!["a.txt".to_string()]).unwrap();
+
+    GitHandler::create_commit(dir.path(), "second", false, true, false).unwrap();
+    assert_eq!(head_summaries(&repo), ["rewritten", "first"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn hooks_are_read_from_core_hooks_path() {
+    let dir = TempDir::new("hook-hooks-path");
+    let repo = init_repo(&dir.0);
+    let first = commit_files(&repo, &[("a.txt", "one\n")], "first");
+    repo.config().unwrap().set_str("core.hooksPath", "custom-hooks").unwrap();
+    write_hook(&dir.0.join("custom-hooks"), "pre-commit", "exit 1");
+    std::fs::write(dir.0.join("a.txt"), "two\n").unwrap();
+    GitHandler::stage_files(dir.path(), vec!["a.txt".to_string()]).unwrap();
+
+    assert_eq!(GitHandler::create_commit(dir.path(), "second", false, true, false), Err("err-hook-failed".to_string()));
+    assert_eq!(repo.head().unwrap().target(), Some(first));
+}
+
+#[test]
+fn submodule_status_and_diff_show_the_new_commits() {
+    let dir = TempDir::new("submodule");
+    let upstream = init_repo(&dir.0.join("lib-src"));
+    let recorded = commit_files(&upstream, &[("lib.txt", "one\n")], "initial");
+    let repo = init_repo(&dir.0.join("main"));
+    commit_files(&repo, &[("a.txt", "one\n")], "first");
+
+    let mut sm = repo.submodule(dir.0.join("lib-src").to_str().unwrap(), Path::new("lib"), true).unwrap();
+    let lib = sm.clone(None).unwrap();
+    sm.add_finalize().unwrap();
+    commit_files(&repo, &[], "add lib");
+
+    let mut config = lib.config().unwrap();
+    config.set_str("user.name", "Test").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+    let second = commit_files(&lib, &[("lib.txt", "two\n")], "second");
+    let third = commit_files(&lib, &[("lib.txt", "three\n")], "third");
+
+    let main_path = dir.0.join("main").to_str().unwrap().to_string();
+    let info = GitHandler::get_submodules(&main_path).unwrap().remove(0);
+    assert_eq!(info.path, "lib");
+    assert_eq!(info.recorded, Some(recorded.to_string()));
+    assert_eq!(info.checked_out, Some(third.to_string()));
+
+    let diff = GitHandler::get_file_diff(&main_path, "lib", &FileDiffOptions::default()).unwrap();
+    let short = |oid: git2::Oid| oid.to_string()[..7].to_string();
+    let lines: Vec<&str> = diff.lines().collect();
+    assert_eq!(lines[0], format!("Subproject commit {}\u{2192}{}", short(recorded), short(third)));
+    assert_eq!(lines[1], format!("+ {} third", short(third)));
+    assert_eq!(lines[2], format!("+ {} second", short(second)));
+    assert_eq!(lines.len(), 3);
+}
+
+#[test]
+fn force_push_preview_lists_the_commits_it_would_drop() {
+    let dir = TempDir::new("preview-force-push");
+    let (url, first) = bare_remote(&dir);
+    let first_path = first.workdir().unwrap().to_str().unwrap().to_string();
+    let second = clone(&url, &dir.0.join("second"));
+    let pushed = commit_files(&second, &[("b.txt", "from second\n")], "from second");
+    GitHandler::push(second.workdir().unwrap().to_str().unwrap(), "", "", "normal", false).unwrap();
+
+    commit_files(&first, &[("a.txt", "two\n")], "from first");
+    GitHandler::fetch(&first_path, "", "").unwrap();
+    let preview = GitHandler::preview_force_push(&first_path).unwrap();
+    assert_eq!(preview.commits, vec![(pushed.to_string(), "from second".to_string())]);
+}
+
+#[test]
+fn delete_branch_preview_skips_commits_kept_by_other_refs() {
+    let dir = TempDir::new("preview-delete-branch");
+    let repo = init_repo(&dir.0);
+    commit_files(&repo, &[("a.txt", "one\n")], "first");
+    let main_ref = repo.head().unwrap().name().unwrap().to_string();
+    let base = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("feature", &base, false).unwrap();
+    repo.set_head("refs/heads/feature").unwrap();
+    let kept = commit_files(&repo, &[("a.txt", "two\n")], "kept elsewhere");
+    let dropped = commit_files(&repo, &[("a.txt", "three\n")], "only on feature");
+    repo.branch("other", &repo.find_commit(kept).unwrap(), false).unwrap();
+    repo.set_head(&main_ref).unwrap();
+
+    let preview = GitHandler::preview_delete_branch(dir.path(), "feature").unwrap();
+    assert_eq!(preview.commits, vec![(dropped.to_string(), "only on feature".to_string())]);
+}
@@ -37,6 +37,25 @@ impl I18nService {
         
         key.to_string()
     }
+
+    /// Translates `key` and fills in its `{ $name }` placeholders from `args`.
+    pub fn translate_with(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let text = self.translate(key);
+        let mut result = String::with_capacity(text.len());
+        let mut rest = text.as_str();
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else { break };
+            let name = rest[start + 1..start + len].trim().strip_prefix('$').unwrap_or_default();
+            result.push_str(&rest[..start]);
+            match args.iter().find(|(arg, _)| *arg == name) {
+                Some((_, value)) => result.push_str(value),
+                None => result.push_str(&rest[start..start + len + 1]),
+            }
+            rest = &rest[start + len + 1..];
+        }
+        result.push_str(rest);
+        result
+    }
 }
 
 fn parse_ftl(content: &str) -> HashMap<String, String> {
@@ -51,4 +70,16 @@ fn parse_ftl(content: &str) -> HashMap<String, String> {
         }
     }
     map
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translate_with_fills_placeholders() {
+        let mut i18n = I18nService::new("en-US");
+        assert_eq!(i18n.translate_with("wip-row", &[("count", "3")]), "Uncommitted changes (3 files)");
+        i18n.translations.insert("en-US".to_string(), parse_ftl("tight = {$a} and { $b } and { $c }"));
+        assert_eq!(i18n.translate_with("tight", &[("a", "1"), ("b", "2")]), "1 and 2 and { $c }");
+    }
+}
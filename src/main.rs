@@ -10,7 +10,9 @@ use dioxus::desktop::{Config, WindowBuilder};
 use dioxus::desktop::tao::platform::windows::WindowBuilderExtWindows;
 use std::path::PathBuf; 
 use std::fs;
+use std::collections::HashMap;
 use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
 
 pub fn get_config_path() -> Option<PathBuf> {
     if let Some(base_dirs) = BaseDirs::new() {
@@ -51,7 +53,160 @@ pub fn save_credentials(user: &str, token: &str, repo_path: &str) -> Result<(),
     }
 }
 
+/// Settings that are remembered separately for every repository.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RepoSettings {
+    #[serde(default)]
+    pub show_all_refs: bool,
+    #[serde(default)]
+    pub hidden_refs: Vec<String>, // Full ref names excluded from the history walk
+    #[serde(default)]
+    pub hide_remotes: bool,
+    #[serde(default = "default_protected_branches")]
+    pub protected_branches: Vec<String>, // Branches whose commits must carry a valid signature
+    #[serde(default)]
+    pub conventional_commits: bool,
+    #[serde(default = "default_conventional_types")]
+    pub conventional_types: Vec<String>,
+    #[serde(default)]
+    pub conventional_scopes: Vec<String>, // Empty allows any scope
+    #[serde(default)]
+    pub block_on_lint_errors: bool, // Otherwise lint errors only need to be confirmed
+}
+
+impl Default for RepoSettings {
+    fn default() -> Self {
+        RepoSettings {
+            show_all_refs: false,
+            hidden_refs: Vec::new(),
+            hide_remotes: false,
+            protected_branches: default_protected_branches(),
+            conventional_commits: false,
+            conventional_types: default_conventional_types(),
+            conventional_scopes: Vec::new(),
+            block_on_lint_errors: false,
+        }
+    }
+}
+
+fn default_protected_branches() -> Vec<String> {
+    vec!["main".to_string(), "master".to_string()]
+}
+
+fn default_conventional_types() -> Vec<String> {
+    git::message::DEFAULT_CONVENTIONAL_TYPES.iter().map(|t| t.to_string()).collect()
+}
+
+fn get_repo_settings_path() -> Option<PathBuf> {
+    get_config_path().map(|p| p.with_file_name("repo_settings.json"))
+}
+
+fn load_all_repo_settings() -> HashMap<String, RepoSettings> {
+    get_repo_settings_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn load_repo_settings(repo_path: &str) -> RepoSettings {
+    load_all_repo_settings().remove(repo_path).unwrap_or_default()
+}
+
+pub fn save_repo_settings(repo_path: &str, settings: &RepoSettings) -> Result<(), String> {
+    let Some(settings_path) = get_repo_settings_path() else {
+        return Err("Konfigurationsverzeichnis konnte nicht ermittelt werden.".to_string());
+    };
+    if let Some(parent_dir) = settings_path.parent() {
+        fs::create_dir_all(parent_dir).map_err(|e| format!("Fehler beim Erstellen des Konfigurationsverzeichnisses: {}", e))?;
+    }
+    let mut all = load_all_repo_settings();
+    all.insert(repo_path.to_string(), settings.clone());
+    let data = serde_json::to_string_pretty(&all).map_err(|e| e.to_string())?;
+    fs::write(&settings_path, data).map_err(|e| format!("Fehler beim Schreiben der Repository-Einstellungen: {}", e))
+}
+
+/// Settings that apply to every repository.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct AppSettings {
+    #[serde(default)]
+    pub skip_confirmations: Vec<String>, // Destructive actions ("reset", "discard", "delete-branch", "force-push") that run without asking
+    #[serde(default)]
+    pub diff_side_by_side: bool,
+}
+
+fn get_app_settings_path() -> Option<PathBuf> {
+    get_config_path().map(|p| p.with_file_name("settings.json"))
+}
+
+pub fn load_app_settings() -> AppSettings {
+    get_app_settings_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save_app_settings(settings: &AppSettings) -> Result<(), String> {
+    let Some(settings_path) = get_app_settings_path() else {
+        return Err("Konfigurationsverzeichnis konnte nicht ermittelt werden.".to_string());
+    };
+    if let Some(parent_dir) = settings_path.parent() {
+        fs::create_dir_all(parent_dir).map_err(|e| format!("Fehler beim Erstellen des Konfigurationsverzeichnisses: {}", e))?;
+    }
+    let data = serde_json::to_string_pretty(settings).map_err(|e| e.to_string())?;
+    fs::write(&settings_path, data).map_err(|e| format!("Fehler beim Schreiben der Einstellungen: {}", e))
+}
+
+fn run_export_graph(args: &[String]) -> i32 {
+    let mut repo = ".".to_string();
+    let mut range = String::new();
+    let mut limit = 100;
+    let mut all_branches = false;
+    let mut options = git::HistoryOptions::default();
+    let mut out = None;
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--repo" => repo = iter.next().cloned().unwrap_or(repo),
+            "--range" => range = iter.next().cloned().unwrap_or_default(),
+            "--limit" => limit = iter.next().and_then(|l| l.parse().ok()).unwrap_or(limit),
+            "--all" => all_branches = true,
+            "--sort" => options.sort_mode = iter.next().cloned().unwrap_or(options.sort_mode),
+            "--first-parent" => options.first_parent = true,
+            "--simplify-by-decoration" => options.simplify_by_decoration = true,
+            "--out" => out = iter.next().map(PathBuf::from),
+            _ => {
+                eprintln!("Unknown argument: {}", arg);
+                out = None;
+                break;
+            }
+        }
+    }
+
+    let Some(out) = out else {
+        eprintln!("Usage: gitamicus export-graph [--repo <path>] [--range <rev|from..to>] [--limit <n>] [--all] [--sort topo|date|author-date] [--first-parent] [--simplify-by-decoration] --out <file.svg|file.png>");
+        return 2;
+    };
+    match git::export::export_graph_to_file(&repo, &range, limit, all_branches, &options, &out) {
+        Ok(_) => {
+            println!("Graph exported to {}", out.display());
+            0
+        }
+        Err(e) => {
+            let system_lang = sys_locale::get_locale().unwrap_or_else(|| "en-US".to_string());
+            let i18n = if system_lang.starts_with("de") { i18n::I18nService::new("de-DE") } else { i18n::I18nService::new("en-US") };
+            eprintln!("Export failed: {}", i18n.translate(&e));
+            1
+        }
+    }
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(|a| a == "export-graph").unwrap_or(false) {
+        std::process::exit(run_export_graph(&args[2..]));
+    }
+
     let custom_head = r#"
         <style>
             @import url('https://fonts.googleapis.com/css2?family=Inter:wght@400;500;600;700&family=JetBrains+Mono:wght@400;500&display=swap');
@@ -165,6 +320,91 @@ fn main() {
                 position: relative;
             }
             .commit-graph-cell-svg svg { display: block; position: absolute; top: 0; left: 0; height: 100%; width: 100%; }
+
+            .ref-badge {
+                display: inline-flex; align-items: center; gap: 4px; margin-right: 6px; padding: 1px 7px;
+                border: 1px solid; border-radius: 10px; font-size: 0.78em; font-weight: 600;
+                font-family: 'JetBrains Mono', monospace; color: var(--text-main); cursor: pointer;
+                max-width: 180px; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; vertical-align: middle;
+            }
+            .ref-badge:hover { filter: brightness(1.3); }
+            .ref-badge.head { font-weight: 800; color: #fff; box-shadow: 0 0 0 1px rgba(255,255,255,0.35); }
+            .wip-row td, .stash-row td { color: var(--text-sub); font-style: italic; cursor: pointer; }
+            tr.dimmed td:not(.commit-graph-cell-svg) { opacity: 0.45; }
+            .contained-in { font-size: 0.8em; color: var(--text-sub); margin-top: 6px; display: flex; flex-wrap: wrap; align-items: center; gap: 4px 0; }
+            .commit-option { display: flex; align-items: center; gap: 5px; font-size: 0.85em; color: var(--text-sub); cursor: pointer; }
+            .commit-option input { margin: 0; accent-color: var(--accent-primary); }
+            
+            .sig-badge { display: inline-block; width: 18px; margin-right: 4px; text-align: center; font-size: 0.85em; cursor: help; }
+            .sig-badge.sig-bad, .sig-badge.sig-unsigned { color: var(--accent-red); }
+            .sig-badge.sig-untrusted, .sig-badge.sig-unverified { color: #f9e2af; }
+            tr.sig-flagged td:first-child { box-shadow: inset 3px 0 0 var(--accent-red); }
+            .signature-line { font-size: 0.8em; margin-top: 6px; }
+            .signature-line.sig-good { color: #a6e3a1; }
+            .signature-line.sig-bad, .signature-line.sig-unsigned { color: var(--accent-red); }
+            .signature-line.sig-untrusted, .signature-line.sig-unverified { color: #f9e2af; }
+            .sig-warning { margin-top: 4px; color: var(--accent-red); font-weight: 600; }
+            
+            .submodule-state { margin-left: auto; font-family: 'JetBrains Mono', monospace; font-size: 0.75em; color: var(--text-sub); }
+            .submodule-state.out-of-sync { color: #f9e2af; }
+            
+            .reflog-selector { font-family: 'JetBrains Mono', monospace; color: var(--accent-primary); white-space: nowrap; }
+            .reflog-sha { font-family: 'JetBrains Mono', monospace; color: var(--text-sub); }
+            .undo-list-title { margin: 10px 0 4px 0; font-weight: 600; color: var(--text-sub); }
+            .undo-commit { padding: 3px 8px; border-left: 3px solid; margin-bottom: 2px; }
+            .undo-commit.restored { border-color: #a6e3a1; }
+            .undo-commit.removed { border-color: var(--accent-red); }
+            .confirm-file { padding: 3px 8px; font-family: 'JetBrains Mono', monospace; border-left: 3px solid var(--accent-secondary); margin-bottom: 2px; word-break: break-all; }
+            .hook-run { border-left: 3px solid #a6e3a1; padding: 6px 10px; margin-bottom: 8px; background: var(--bg-base); }
+            .hook-run.failed { border-color: var(--accent-red); }
+            .hook-run-header { display: flex; gap: 10px; align-items: center; }
+            .hook-output { margin: 6px 0 0 0; font-family: 'JetBrains Mono', monospace; font-size: 0.9em; white-space: pre-wrap; color: var(--text-sub); max-height: 200px; overflow-y: auto; }
+            .commit-subject, .commit-body { width: 100%; padding: 8px 10px; border: 1px solid var(--border-color); border-radius: 4px; font-size: 0.85em; font-family: 'JetBrains Mono', monospace; background-color: var(--bg-surface); color: white; outline: none; background-attachment: local; }
+            .commit-subject { flex: 1; background-image: linear-gradient(to right, transparent calc(10px + 50ch), var(--border-color) calc(10px + 50ch), var(--border-color) calc(11px + 50ch), transparent calc(11px + 50ch)); }
+            .commit-body { height: 90px; resize: vertical; background-image: linear-gradient(to right, transparent calc(10px + 72ch), var(--border-color) calc(10px + 72ch), var(--border-color) calc(11px + 72ch), transparent calc(11px + 72ch)); }
+            .subject-counter { font-size: 0.75em; color: var(--text-sub); font-family: 'JetBrains Mono', monospace; }
+            .subject-counter.over { color: var(--accent-secondary); }
+            .lint-list { font-size: 0.8em; display: flex; flex-direction: column; gap: 2px; }
+            .lint-issue { color: #f9e2af; }
+            .lint-issue.error { color: var(--accent-red); }
+            .trailer-options { display: flex; align-items: center; gap: 8px; flex-wrap: wrap; }
+            .co-author-chip { font-size: 0.75em; background: rgba(137, 180, 250, 0.15); border: 1px solid var(--border-color); border-radius: 10px; padding: 2px 8px; display: inline-flex; align-items: center; gap: 6px; }
+            .chip-remove { cursor: pointer; color: var(--text-sub); }
+            .chip-remove:hover { color: var(--accent-red); }
+            .trailer-list { margin-top: 8px; font-size: 0.85em; display: flex; flex-direction: column; gap: 2px; }
+            .trailer-key { display: inline-block; min-width: 130px; color: var(--text-sub); font-family: 'JetBrains Mono', monospace; }
+            .amend-warning { font-size: 0.8em; color: var(--accent-secondary); }
+            .rewrite-note { font-size: 0.8em; color: var(--text-sub); margin-bottom: 10px; }
+            .split-files { flex: 1; overflow-y: auto; min-height: 120px; border: 1px solid var(--border-color); border-radius: 6px; margin: 10px 0; }
+            .split-file-header { display: flex; align-items: center; gap: 6px; padding: 6px 10px; font-weight: 600; font-size: 0.85em; background: var(--bg-base); border-bottom: 1px solid var(--border-color); }
+            .split-hunk { display: flex; align-items: flex-start; gap: 6px; padding: 4px 10px; border-bottom: 1px solid var(--border-color); }
+            .split-hunk.taken { opacity: 0.5; }
+            .split-hunk pre { margin: 0; font-size: 0.8em; white-space: pre-wrap; word-break: break-all; }
+            .split-part-badge { font-size: 0.75em; color: var(--accent-primary); min-width: 20px; }
+            .split-part { display: flex; justify-content: space-between; font-size: 0.85em; padding: 3px 0; }
+            
+            .diff-word-add { background: rgba(166, 227, 161, 0.45); border-radius: 2px; }
+            .diff-word-del { background: rgba(243, 139, 168, 0.45); border-radius: 2px; }
+            .syn-comment { color: #6c7086; font-style: italic; }
+            .syn-string { color: #a6e3a1; }
+            .syn-number { color: #fab387; }
+            .syn-constant { color: #fab387; }
+            .syn-keyword { color: #cba6f7; }
+            .syn-function { color: var(--accent-primary); }
+            .syn-type { color: #f9e2af; }
+            .diff-toolbar { display: flex; justify-content: flex-end; align-items: center; gap: 12px; padding: 4px 10px; background: var(--bg-header); border-bottom: 1px solid var(--border-color); flex-shrink: 0; }
+            .diff-split { display: grid; grid-template-columns: auto 1fr auto 1fr; line-height: 1.4; }
+            .diff-split-header { grid-column: 1 / -1; white-space: pre; color: var(--text-sub); }
+            .diff-num { color: var(--text-sub); text-align: right; padding: 0 8px; user-select: none; opacity: 0.7; }
+            .diff-cell { white-space: pre-wrap; word-break: break-all; padding-right: 8px; }
+            .diff-cell.added { background: rgba(166, 227, 161, 0.2); }
+            .diff-cell.removed { background: rgba(243, 139, 168, 0.2); }
+            .diff-cell.empty { background: rgba(0, 0, 0, 0.2); }
+            .diff-gutter { display: inline-block; min-width: 4ch; padding-right: 8px; text-align: right; color: var(--text-sub); opacity: 0.7; user-select: none; }
+            .diff-context-input { width: 50px; margin-left: 6px; padding: 2px 4px; }
+            
+            .snapshot-item { display: flex; align-items: center; gap: 10px; padding: 10px; border-bottom: 1px solid var(--border-color); }
+            .snapshot-files { font-size: 0.8em; color: var(--text-sub); white-space: nowrap; overflow: hidden; text-overflow: ellipsis; margin-top: 2px; }
             
             input, textarea { 
                 background: var(--bg-surface); border: 1px solid var(--border-color); color: white; 
@@ -211,6 +451,12 @@ fn main() {
                 color: var(--bg-base);
                 font-weight: 600;
             }
+            .nav-item .ref-visibility { margin: 0 8px 0 0; cursor: pointer; accent-color: var(--accent-primary); }
+            .workspace-header .header-action {
+                margin-left: auto; cursor: pointer; text-transform: none; font-weight: 600;
+                color: var(--accent-primary); letter-spacing: 0;
+            }
+            .workspace-header .header-action:hover { text-decoration: underline; }
             .nav-item .badge {
                 background-color: var(--bg-base);
                 color: var(--accent-primary);
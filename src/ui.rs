@@ -2,6 +2,8 @@
 // This module will contain UI helper functions and component rendering logic
 // that can be extracted from the main app component
 
+pub mod highlight;
+
 pub mod components {
     // Future home of extracted UI components
     // For now, all UI rendering remains in app.rs within the rsx! macro
@@ -0,0 +1,157 @@
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+use syntect::parsing::{ParseState, Scope, ScopeStack, SyntaxReference, SyntaxSet};
+
+use crate::git::diff::{self, DiffLine, LineKind, Segment};
+
+// Larger diffs are shown without syntax colors to keep rendering fast
+const HIGHLIGHT_LINE_LIMIT: usize = 5000;
+
+// Scope prefixes and the CSS class they get, checked from the innermost scope outwards
+const SCOPE_CLASSES: &[(&str, &str)] = &[
+    ("comment", "syn-comment"),
+    ("string", "syn-string"),
+    ("constant.numeric", "syn-number"),
+    ("constant.character", "syn-string"),
+    ("constant.language", "syn-constant"),
+    ("keyword", "syn-keyword"),
+    ("storage", "syn-keyword"),
+    ("entity.name.tag", "syn-keyword"),
+    ("entity.name.function", "syn-function"),
+    ("support.function", "syn-function"),
+    ("variable.function", "syn-function"),
+    ("entity.name.type", "syn-type"),
+    ("entity.name.class", "syn-type"),
+    ("entity.name.struct", "syn-type"),
+    ("entity.name.enum", "syn-type"),
+    ("support.type", "syn-type"),
+    ("support.class", "syn-type"),
+    ("entity.other.attribute-name", "syn-type"),
+];
+
+static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+static SCOPES: OnceLock<Vec<(Scope, &'static str)>> = OnceLock::new();
+// The last highlighted diff; the app renders far more often than the diff changes
+static LAST_DIFF: Mutex<Option<(String, String, Vec<DiffLine>)>> = Mutex::new(None);
+
+fn syntaxes() -> &'static SyntaxSet {
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn scopes() -> &'static [(Scope, &'static str)] {
+    SCOPES.get_or_init(|| SCOPE_CLASSES.iter().filter_map(|(s, class)| Scope::new(s).ok().map(|scope| (scope, *class))).collect())
+}
+
+/// Parses a diff of `path` with changed words marked and syntax colors from the
+/// grammar of the file's extension.
+pub fn diff_lines(path: &str, diff_text: &str) -> Vec<DiffLine> {
+    if let Ok(last) = LAST_DIFF.lock() {
+        if let Some((p, text, lines)) = last.as_ref() {
+            if p == path && text == diff_text {
+                return lines.clone();
+            }
+        }
+    }
+    let mut lines = diff::parse(diff_text);
+    if let Some(syntax) = syntax_for(path) {
+        if lines.len() <= HIGHLIGHT_LINE_LIMIT {
+            highlight(&mut lines, syntax);
+        }
+    }
+    if let Ok(mut last) = LAST_DIFF.lock() {
+        *last = Some((path.to_string(), diff_text.to_string(), lines.clone()));
+    }
+    lines
+}
+
+fn syntax_for(path: &str) -> Option<&'static SyntaxReference> {
+    let path = Path::new(path);
+    let ss = syntaxes();
+    path.extension().and_then(|e| ss.find_syntax_by_extension(&e.to_string_lossy()))
+        .or_else(|| path.file_name().and_then(|n| ss.find_syntax_by_extension(&n.to_string_lossy())))
+}
+
+/// Colors the code of each line. The old and new side are parsed separately so that
+/// a removed line does not leave the new side inside an unclosed string or comment.
+fn highlight(lines: &mut [DiffLine], syntax: &SyntaxReference) {
+    let ss = syntaxes();
+    let mut old = (ParseState::new(syntax), ScopeStack::new());
+    let mut new = (ParseState::new(syntax), ScopeStack::new());
+    for line in lines.iter_mut() {
+        let code = line.text().get(1..).unwrap_or_default().to_string() + "\n";
+        let classes = match line.kind {
+            LineKind::Header => {
+                // Each hunk starts somewhere else in the file
+                old = (ParseState::new(syntax), ScopeStack::new());
+                new = (ParseState::new(syntax), ScopeStack::new());
+                continue;
+            }
+            LineKind::Marker => continue,
+            LineKind::Removed => classify(&code, &mut old, ss),
+            LineKind::Added => classify(&code, &mut new, ss),
+            LineKind::Context => {
+                classify(&code, &mut old, ss);
+                classify(&code, &mut new, ss)
+            }
+        };
+        line.segments = apply_classes(&line.segments, &classes);
+    }
+}
+
+/// Byte ranges of `code` (start, end, CSS class), in order.
+fn classify(code: &str, (state, stack): &mut (ParseState, ScopeStack), ss: &SyntaxSet) -> Vec<(usize, usize, &'static str)> {
+    let Ok(ops) = state.parse_line(code, ss) else { return Vec::new() };
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (offset, op) in ops {
+        if offset > start {
+            ranges.push((start, offset, class_of(stack)));
+        }
+        start = offset;
+        let _ = stack.apply(&op);
+    }
+    let end = code.len() - 1; // Without the added newline
+    if end > start {
+        ranges.push((start, end, class_of(stack)));
+    }
+    ranges
+}
+
+fn class_of(stack: &ScopeStack) -> &'static str {
+    for scope in stack.as_slice().iter().rev() {
+        if let Some((_, class)) = scopes().iter().find(|(prefix, _)| prefix.is_prefix_of(*scope)) {
+            return class;
+        }
+    }
+    ""
+}
+
+/// Cuts the segments where the class changes. Offsets in `classes` start after the
+/// one-character diff prefix.
+fn apply_classes(segments: &[Segment], classes: &[(usize, usize, &'static str)]) -> Vec<Segment> {
+    let len: usize = segments.iter().map(|s| s.text.len()).sum();
+    let mut byte_class = vec![""; len];
+    for &(start, end, class) in classes {
+        for c in byte_class.iter_mut().take(end + 1).skip(start + 1) {
+            *c = class;
+        }
+    }
+
+    let mut result: Vec<Segment> = Vec::new();
+    let mut pos = 0;
+    for segment in segments {
+        let mut start = 0;
+        for (i, _) in segment.text.char_indices().skip(1) {
+            if byte_class[pos + i] != byte_class[pos + start] {
+                result.push(Segment { text: segment.text[start..i].to_string(), changed: segment.changed, syntax: byte_class[pos + start] });
+                start = i;
+            }
+        }
+        if start < segment.text.len() {
+            result.push(Segment { text: segment.text[start..].to_string(), changed: segment.changed, syntax: byte_class[pos + start] });
+        }
+        pos += segment.text.len();
+    }
+    result
+}